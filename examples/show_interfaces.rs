@@ -2,7 +2,7 @@
 
 use nl80211::Socket;
 
-fn main() -> Result<(), neli::err::NlError> {
+fn main() -> Result<(), nl80211::Nl80211Error> {
     let interfaces = Socket::connect()?.get_interfaces_info()?;
 
     for interface in interfaces {
@@ -287,6 +287,14 @@ impl_var_trait!(
     IftypeMax         => 12
 );
 
+// The enum is declared by impl_var_trait!, which can't mark a default variant
+#[allow(clippy::derivable_impls)]
+impl Default for Nl80211Iftype {
+    fn default() -> Self {
+        Nl80211Iftype::IftypeUnspecified
+    }
+}
+
 impl_var_trait!(
     // nl80211StaFlags as declared in nl80211/nl80211.h:2428
     Nl80211StaFlags, u16, NlAttrType,
@@ -330,8 +338,13 @@ impl_var_trait!(
     RateInfo160MhzWidth   => 10,
     RateInfo10MhzWidth    => 11,
     RateInfo5MhzWidth     => 12,
-    RateInfoAfterLast     => 13,
-    RateInfoMax           => 12
+    RateInfoHeMcs         => 13,
+    RateInfoHeNss         => 14,
+    RateInfoHeGi          => 15,
+    RateInfoHeDcm         => 16,
+    RateInfoHeRuAlloc     => 17,
+    RateInfoAfterLast     => 18,
+    RateInfoMax           => 17
 );
 
 impl_var_trait!(
@@ -388,8 +401,14 @@ impl_var_trait!(
     StaInfoTidStats           => 31,
     StaInfoRxDuration         => 32,
     StaInfoPad                => 33,
-    StaInfoAfterLast          => 34,
-    StaInfoMax                => 33
+    StaInfoAckSignal          => 34,
+    StaInfoAckSignalAvg       => 35,
+    StaInfoRxMpdus            => 36,
+    StaInfoFcsErrorCount      => 37,
+    StaInfoConnectedToGate    => 38,
+    StaInfoTxDuration         => 39,
+    StaInfoAfterLast          => 40,
+    StaInfoMax                => 39
 );
 
 impl_var_trait!(
@@ -403,8 +422,30 @@ impl_var_trait!(
     TidStatsTxMsduRetries => 3,
     TidStatsTxMsduFailed  => 4,
     TidStatsPad           => 5,
-    NumTidStats           => 6,
-    TidStatsMax           => 5
+    TidStatsTxqStats      => 6,
+    NumTidStats           => 7,
+    TidStatsMax           => 6
+);
+
+impl_var_trait!(
+    /// nl80211TxqStats, nested in TidStatsTxqStats
+    ///
+    /// Enumeration from nl80211/nl80211.h:1129
+    Nl80211TxqStats, u16, NlAttrType,
+    TxqStatsInvalid        => 0,
+    TxqStatsBacklogBytes   => 1,
+    TxqStatsBacklogPackets => 2,
+    TxqStatsFlows          => 3,
+    TxqStatsDrops          => 4,
+    TxqStatsEcnMarks       => 5,
+    TxqStatsOverlimit      => 6,
+    TxqStatsOvermemory     => 7,
+    TxqStatsCollisions     => 8,
+    TxqStatsTxBytes        => 9,
+    TxqStatsTxPackets      => 10,
+    TxqStatsMaxFlows       => 11,
+    NumTxqStats            => 12,
+    TxqStatsMax            => 11
 );
 
 impl_var_trait!(
@@ -450,8 +491,24 @@ impl_var_trait!(
     BandAttrHtAmpduDensity => 6,
     BandAttrVhtMcsSet      => 7,
     BandAttrVhtCapa        => 8,
-    BandAttrAfterLast      => 9,
-    BandAttrMax            => 8
+    BandAttrIftypeData     => 9,
+    BandAttrAfterLast      => 10,
+    BandAttrMax            => 9
+);
+
+impl_var_trait!(
+    /// nl80211BandIftypeAttr, nested in BandAttrIftypeData
+    ///
+    /// Enumeration from nl80211/nl80211.h:3957
+    Nl80211BandIftypeAttr, u16, NlAttrType,
+    BandIftypeAttrInvalid     => 0,
+    BandIftypeAttrIftypes     => 1,
+    BandIftypeAttrHeCapMac    => 2,
+    BandIftypeAttrHeCapPhy    => 3,
+    BandIftypeAttrHeCapMcsSet => 4,
+    BandIftypeAttrHeCapPpe    => 5,
+    BandIftypeAttrAfterLast   => 6,
+    BandIftypeAttrMax         => 5
 );
 
 impl_var_trait!(
@@ -827,7 +884,8 @@ impl_var_trait!(
     /// Enumeration from nl80211/nl80211.h:3656
     Nl80211Mfp, u16, NlAttrType,
     MfpNo       => 0,
-    MfpRequired => 1
+    MfpRequired => 1,
+    MfpOptional => 2
 );
 
 impl_var_trait!(
@@ -836,7 +894,8 @@ impl_var_trait!(
     /// Enumeration from nl80211/nl80211.h:3661
     Nl80211WpaVersions, u16, NlAttrType,
     WpaVersion1 => 1 << 0,
-    WpaVersion2 => 1 << 1
+    WpaVersion2 => 1 << 1,
+    WpaVersion3 => 1 << 2
 );
 
 impl_var_trait!(
@@ -900,7 +959,8 @@ impl_var_trait!(
     Band2ghz  => 0,
     Band5ghz  => 1,
     Band60ghz => 2,
-    NumBands  => 3
+    Band6ghz  => 3,
+    NumBands  => 4
 );
 
 impl_var_trait!(
@@ -1,36 +1,172 @@
 use std::fmt;
-use crate::attr::Nl80211Attr;
-use crate::attr::Nl80211Bss;
+use std::time::Duration;
+use std::convert::{TryFrom, TryInto};
+use crate::attr::{Nl80211Attr, Nl80211Bss, Nl80211BssScanWidth, Nl80211BssStatus};
+use crate::error::Nl80211Error;
+use crate::ie::{Element, Elements};
 use crate::nl80211traits::ParseNlAttr;
-use crate::parse_attr::{parse_hex, parse_i32, parse_u16, parse_u32};
+use crate::parse_attr::{fixed_payload, parse_i32, parse_u16, parse_u32, parse_u64, parse_u8};
+use crate::security::Security;
+use crate::types::{MacAddr, Mbm, Mhz, Ssid, Tu};
 use neli::nlattr::AttrHandle;
 
 /// A struct representing a BSS (Basic Service Set)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Bss {
-    pub bssid: Option<Vec<u8>>,
-    /// Frequency in MHz (u32)
-    pub frequency: Option<Vec<u8>>,
-    /// Beacon interval of the (I)BSS (u16)
-    pub beacon_interval: Option<Vec<u8>>,
-    /// Age of this BSS entry in ms (u32)
-    pub seen_ms_ago: Option<Vec<u8>>,
-    /// Status, if this BSS is "used" (u8)
-    pub status: Option<Vec<u8>>,
-    /// Signal strength of probe response/beacon in mBm (100 * dBm) (i32)
-    pub signal: Option<Vec<u8>>,
+    /// BSSID of the BSS
+    pub bssid: Option<MacAddr>,
+    /// Frequency of the channel
+    pub frequency: Option<Mhz>,
+    /// TSF of the received probe response or beacon, in microseconds
+    pub tsf: Option<u64>,
+    /// TSF of the last received beacon, in microseconds
+    pub beacon_tsf: Option<u64>,
+    /// The TSF and information elements come from a probe response
+    pub presp_data: bool,
+    /// Beacon interval of the (I)BSS
+    pub beacon_interval: Option<Tu>,
+    /// Capability information field
+    pub capability: Option<Capability>,
+    /// Channel width of the scan
+    pub chan_width: Option<Nl80211BssScanWidth>,
+    /// Age of this BSS entry in ms
+    pub seen_ms_ago: Option<u32>,
+    /// Boottime when this BSS entry was last updated
+    pub last_seen_boottime: Option<Duration>,
+    /// Status, if this BSS is "used"
+    pub status: Option<Nl80211BssStatus>,
+    /// Signal strength of probe response/beacon
+    pub signal: Option<Mbm>,
+    /// Signal strength of probe response/beacon, in unspecified units scaled to 0..100
+    pub signal_unspec: Option<u8>,
+    /// TSF of the reporting BSS at the time of `parent_tsf`, for scans offloaded to another BSS
+    pub parent_tsf: Option<u64>,
+    /// BSSID of the BSS whose TSF is `parent_tsf`
+    pub parent_bssid: Option<MacAddr>,
+    /// Information elements of the last received probe response or beacon
+    pub information_elements: Option<Vec<u8>>,
+    /// Information elements of the last received beacon
+    pub beacon_ies: Option<Vec<u8>>,
 }
 
 impl Bss {
-    pub fn default() -> Bss {
-        Bss {
-            bssid: None,
-            frequency: None,
-            beacon_interval: None,
-            seen_ms_ago: None,
-            status: None,
-            signal: None,
+    /// Decoded information elements, from the last probe response or else from the last beacon
+    pub fn elements(&self) -> Elements<'_> {
+        let ies = self.information_elements.as_ref().or(self.beacon_ies.as_ref());
+        Elements::new(ies.map_or(&[][..], Vec::as_slice))
+    }
+
+    /// SSID advertised by the BSS, empty for hidden networks
+    pub fn ssid(&self) -> Option<Ssid> {
+        self.elements().find_map(|element| match element {
+            Element::Ssid(ssid) => Some(ssid),
+            _ => None,
+        })
+    }
+
+    /// Security of the BSS, from its RSN and WPA elements
+    pub fn security(&self) -> Security {
+        let mut rsn = None;
+        let mut wpa = None;
+        for element in self.elements() {
+            match element {
+                Element::Rsn(element) if rsn.is_none() => rsn = Some(element),
+                Element::Vendor(vendor) if wpa.is_none() => wpa = vendor.wpa(),
+                _ => (),
+            }
+        }
+
+        let privacy = self.capability.is_some_and(|capability| capability.privacy());
+        Security::classify(privacy, rsn.as_ref(), wpa.as_ref())
+    }
+}
+
+/// Capability information field of a beacon or probe response
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capability(pub u16);
+
+impl Capability {
+    pub const ESS: u16 = 1 << 0;
+    pub const IBSS: u16 = 1 << 1;
+    pub const CF_POLLABLE: u16 = 1 << 2;
+    pub const CF_POLL_REQUEST: u16 = 1 << 3;
+    pub const PRIVACY: u16 = 1 << 4;
+    pub const SHORT_PREAMBLE: u16 = 1 << 5;
+    pub const PBCC: u16 = 1 << 6;
+    pub const CHANNEL_AGILITY: u16 = 1 << 7;
+    pub const SPECTRUM_MGMT: u16 = 1 << 8;
+    pub const QOS: u16 = 1 << 9;
+    pub const SHORT_SLOT_TIME: u16 = 1 << 10;
+    pub const APSD: u16 = 1 << 11;
+    pub const RADIO_MEASURE: u16 = 1 << 12;
+    pub const DSSS_OFDM: u16 = 1 << 13;
+    pub const DELAYED_BACK: u16 = 1 << 14;
+    pub const IMMEDIATE_BACK: u16 = 1 << 15;
+
+    const NAMES: [&'static str; 16] = [
+        "ESS",
+        "IBSS",
+        "CfPollable",
+        "CfPollReq",
+        "Privacy",
+        "ShortPreamble",
+        "PBCC",
+        "ChannelAgility",
+        "SpectrumMgmt",
+        "QoS",
+        "ShortSlotTime",
+        "APSD",
+        "RadioMeasure",
+        "DSSS-OFDM",
+        "DelayedBACK",
+        "ImmediateBACK",
+    ];
+
+    /// Whether every bit of `bits` is set
+    pub fn contains(&self, bits: u16) -> bool {
+        self.0 & bits == bits
+    }
+
+    /// The BSS is an infrastructure network
+    pub fn ess(&self) -> bool {
+        self.contains(Capability::ESS)
+    }
+
+    /// The BSS is an ad-hoc network
+    pub fn ibss(&self) -> bool {
+        self.contains(Capability::IBSS)
+    }
+
+    /// Data frames are encrypted
+    pub fn privacy(&self) -> bool {
+        self.contains(Capability::PRIVACY)
+    }
+
+    /// Short preamble is allowed
+    pub fn short_preamble(&self) -> bool {
+        self.contains(Capability::SHORT_PREAMBLE)
+    }
+
+    /// Spectrum management (802.11h) is required
+    pub fn spectrum_mgmt(&self) -> bool {
+        self.contains(Capability::SPECTRUM_MGMT)
+    }
+
+    /// Short slot time is used
+    pub fn short_slot_time(&self) -> bool {
+        self.contains(Capability::SHORT_SLOT_TIME)
+    }
+}
+
+impl fmt::Display for Capability {
+    /// Same format as iw, e.g. "ESS Privacy ShortSlotTime (0x0411)"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (bit, name) in Capability::NAMES.iter().enumerate() {
+            if self.contains(1 << bit) {
+                write!(f, "{} ", name)?;
+            }
         }
+        write!(f, "({:#06x})", self.0)
     }
 }
 
@@ -39,36 +175,53 @@ impl fmt::Display for Bss {
         let mut result = Vec::new();
 
         if let Some(bssid) = &self.bssid {
-            result.push(format!("bssid : {}", parse_hex(bssid)))
+            result.push(format!("bssid : {}", bssid))
+        };
+
+        if let Some(ssid) = self.ssid() {
+            result.push(format!("ssid : {}", ssid))
         };
 
         if let Some(frequency) = &self.frequency {
-            result.push(format!(
-                "frequency : {} Ghz",
-                parse_u32(frequency) as f32 / 1000.00
-            ))
+            result.push(format!("frequency : {} Ghz", frequency.ghz()))
+        };
+
+        if let Some(tsf) = &self.tsf {
+            result.push(format!("tsf : {} usec", tsf))
         };
 
         if let Some(beacon_interval) = &self.beacon_interval {
-            result.push(format!(
-                "beacon interval : {} TUs",
-                parse_u16(beacon_interval)
-            ))
+            result.push(format!("beacon interval : {}", beacon_interval))
+        };
+
+        if let Some(capability) = &self.capability {
+            result.push(format!("capability : {}", capability))
         };
 
         if let Some(seen_ms_ago) = &self.seen_ms_ago {
-            result.push(format!("last seen : {} ms", parse_u32(seen_ms_ago)))
+            result.push(format!("last seen : {} ms", seen_ms_ago))
         };
 
         if let Some(status) = &self.status {
-            result.push(format!("status : {}", parse_u32(status)))
+            let status = match status {
+                Nl80211BssStatus::BssStatusAuthenticated => "authenticated".to_string(),
+                Nl80211BssStatus::BssStatusAssociated => "associated".to_string(),
+                Nl80211BssStatus::BssStatusIbssJoined => "joined".to_string(),
+                other => format!("{:?}", other),
+            };
+            result.push(format!("status : {}", status))
         };
 
         if let Some(signal) = &self.signal {
-            result.push(format!(
-                "signal : {:?} dBm",
-                parse_i32(signal) as f32 / 100.00
-            ))
+            result.push(format!("signal : {}", signal))
+        };
+
+        if let Some(signal_unspec) = &self.signal_unspec {
+            result.push(format!("signal : {}/100", signal_unspec))
+        };
+
+        if self.information_elements.is_some() || self.beacon_ies.is_some() {
+            result.push(format!("security : {}", self.security()))
         };
 
         write!(f, "{}", result.join("\n"))
@@ -77,36 +230,58 @@ impl fmt::Display for Bss {
 
 impl ParseNlAttr for Bss {
     /// Parse netlink messages returned by the nl80211 command CmdGetScan
-    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Bss {
+    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Result<Bss, Nl80211Error> {
         for attr in handle.iter() {
-            println!("{:?}", attr);
-            match attr.nla_type {
-                Nl80211Attr::AttrBss => {
-                    let sub_handle = attr.get_nested_attributes::<Nl80211Bss>().unwrap();
-                    for sub_attr in sub_handle.iter() {
-                        match sub_attr.nla_type {
-                            Nl80211Bss::BssBeaconInterval => {
-                                self.beacon_interval = Some(sub_attr.payload.clone())
-                            }
-                            Nl80211Bss::BssFrequency => {
-                                self.frequency = Some(sub_attr.payload.clone())
-                            }
-                            Nl80211Bss::BssSeenMsAgo => {
-                                self.seen_ms_ago = Some(sub_attr.payload.clone())
-                            }
-                            Nl80211Bss::BssStatus => self.status = Some(sub_attr.payload.clone()),
-                            Nl80211Bss::BssBssid => self.bssid = Some(sub_attr.payload.clone()),
-                            Nl80211Bss::BssSignalMbm => {
-                                self.signal = Some(sub_attr.payload.clone())
-                            }
-                            _ => (),
-                        }
+            if attr.nla_type != Nl80211Attr::AttrBss {
+                continue;
+            }
+
+            let sub_handle = attr.get_nested_attributes::<Nl80211Bss>()?;
+
+            for sub_attr in sub_handle.iter() {
+                match sub_attr.nla_type {
+                    Nl80211Bss::BssBssid => {
+                        self.bssid = Some(MacAddr(fixed_payload(sub_attr, 6)?.try_into().unwrap()))
+                    }
+                    Nl80211Bss::BssFrequency => self.frequency = Some(Mhz(parse_u32(sub_attr)?)),
+                    Nl80211Bss::BssTsf => self.tsf = Some(parse_u64(sub_attr)?),
+                    Nl80211Bss::BssBeaconTsf => self.beacon_tsf = Some(parse_u64(sub_attr)?),
+                    Nl80211Bss::BssPrespData => self.presp_data = true,
+                    Nl80211Bss::BssBeaconInterval => {
+                        self.beacon_interval = Some(Tu(parse_u16(sub_attr)? as u32))
+                    }
+                    Nl80211Bss::BssCapability => {
+                        self.capability = Some(Capability(parse_u16(sub_attr)?))
+                    }
+                    Nl80211Bss::BssChanWidth => {
+                        // Values too large for the enumeration are unknown, not truncated
+                        let width = u16::try_from(parse_u32(sub_attr)?).unwrap_or(u16::MAX);
+                        self.chan_width = Some(Nl80211BssScanWidth::from(width))
                     }
+                    Nl80211Bss::BssSeenMsAgo => self.seen_ms_ago = Some(parse_u32(sub_attr)?),
+                    Nl80211Bss::BssLastSeenBoottime => {
+                        self.last_seen_boottime = Some(Duration::from_nanos(parse_u64(sub_attr)?))
+                    }
+                    Nl80211Bss::BssStatus => {
+                        let status = u16::try_from(parse_u32(sub_attr)?).unwrap_or(u16::MAX);
+                        self.status = Some(Nl80211BssStatus::from(status))
+                    }
+                    Nl80211Bss::BssSignalMbm => self.signal = Some(Mbm(parse_i32(sub_attr)?)),
+                    Nl80211Bss::BssSignalUnspec => self.signal_unspec = Some(parse_u8(sub_attr)?),
+                    Nl80211Bss::BssParentTsf => self.parent_tsf = Some(parse_u64(sub_attr)?),
+                    Nl80211Bss::BssParentBssid => {
+                        let parent_bssid = fixed_payload(sub_attr, 6)?.try_into().unwrap();
+                        self.parent_bssid = Some(MacAddr(parent_bssid))
+                    }
+                    Nl80211Bss::BssInformationElements => {
+                        self.information_elements = Some(sub_attr.payload.clone())
+                    }
+                    Nl80211Bss::BssBeaconIes => self.beacon_ies = Some(sub_attr.payload.clone()),
+                    _ => (),
                 }
-                _ => (),
             }
         }
-        self.to_owned()
+        Ok(self.to_owned())
     }
 }
 
@@ -119,20 +294,25 @@ mod test_bss {
     #[test]
     fn test_pretty_format() {
         let bss = Bss {
-            bssid: Some(vec![255, 255, 255, 255, 255, 255]),
-            frequency: Some(vec![108, 9, 0, 0]),
-            beacon_interval: Some(vec![100, 0]),
-            seen_ms_ago: Some(vec![100, 0, 0, 0]),
-            status: Some(vec![1, 0, 0, 0]),
-            signal: Some(vec![76, 235, 255, 255]),
+            bssid: Some(MacAddr([255, 255, 255, 255, 255, 255])),
+            frequency: Some(Mhz(2412)),
+            tsf: Some(170244508804),
+            beacon_interval: Some(Tu(100)),
+            capability: Some(Capability(0x0411)),
+            seen_ms_ago: Some(100),
+            status: Some(Nl80211BssStatus::BssStatusAssociated),
+            signal: Some(Mbm(-5300)),
+            ..Bss::default()
         };
 
         let expected_output = r#"bssid : FF:FF:FF:FF:FF:FF
         frequency : 2.412 Ghz
-        beacon interval : 100 TUs
+        tsf : 170244508804 usec
+        beacon interval : 100 TU
+        capability : ESS Privacy ShortSlotTime (0x0411)
         last seen : 100 ms
-        status : 1
-        signal : -53.0 dBm"#;
+        status : associated
+        signal : -53.00 dBm"#;
 
         assert_eq!(
             format!("{}", bss),
@@ -200,16 +380,61 @@ mod test_bss {
             },
         ];
 
-        let bss = Bss::default().parse(neli::nlattr::AttrHandle::Owned(handler));
+        let bss = Bss::default()
+            .parse(neli::nlattr::AttrHandle::Owned(handler))
+            .unwrap();
         let expected_bss = Bss {
-            bssid: Some(vec![255, 255, 255, 255, 255, 255]),
-            frequency: Some(vec![108, 9, 0, 0]),
-            beacon_interval: Some(vec![100, 0]),
-            seen_ms_ago: Some(vec![100, 0, 0, 0]),
-            status: Some(vec![1, 0, 0, 0]),
-            signal: Some(vec![76, 235, 255, 255]),
+            bssid: Some(MacAddr([255, 255, 255, 255, 255, 255])),
+            frequency: Some(Mhz(2412)),
+            tsf: Some(170244508804),
+            beacon_tsf: Some(170246043323),
+            presp_data: true,
+            beacon_interval: Some(Tu(100)),
+            capability: Some(Capability(0x1511)),
+            chan_width: Some(Nl80211BssScanWidth::BssChanWidth20),
+            seen_ms_ago: Some(100),
+            status: Some(Nl80211BssStatus::BssStatusAssociated),
+            signal: Some(Mbm(-5300)),
+            information_elements: bss.information_elements.clone(),
+            beacon_ies: bss.beacon_ies.clone(),
+            ..Bss::default()
         };
 
+        assert_eq!(bss.information_elements.as_ref().map(Vec::len), Some(347));
+        assert_eq!(bss.beacon_ies.as_ref().map(Vec::len), Some(271));
+        assert_eq!(bss.ssid(), Some(Ssid(b"SFR-1c28".to_vec())));
+        assert_eq!(bss.security(), Security::Wpa2Psk);
+        assert!(bss.capability.unwrap().ess());
+        assert!(bss.capability.unwrap().privacy());
+        assert!(!bss.capability.unwrap().short_preamble());
         assert_eq!(bss, expected_bss)
     }
+
+    #[test]
+    fn test_parse_invalid_bssid() {
+        for nla_type in [Nl80211Bss::BssBssid, Nl80211Bss::BssParentBssid] {
+            // A 5 bytes long nested attribute, padded
+            let mut payload = vec![9, 0];
+            payload.extend_from_slice(&u16::from(nla_type).to_le_bytes());
+            payload.extend_from_slice(&[255, 255, 255, 255, 255, 0, 0, 0]);
+            let handler = vec![Nlattr::new(None, AttrBss, payload).unwrap()];
+
+            match Bss::default().parse(neli::nlattr::AttrHandle::Owned(handler)) {
+                Err(Nl80211Error::InvalidAttribute(err)) => assert_eq!(err.actual, 5),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_out_of_range_status() {
+        // BssStatus nested attribute holding 0x10001, which is not BssStatusAssociated
+        let payload = vec![8, 0, 9, 0, 1, 0, 1, 0];
+        let handler = vec![Nlattr::new(None, AttrBss, payload).unwrap()];
+
+        let bss = Bss::default()
+            .parse(neli::nlattr::AttrHandle::Owned(handler))
+            .unwrap();
+        assert_eq!(bss.status, Some(Nl80211BssStatus::UnrecognizedVariant(u16::MAX)));
+    }
 }
@@ -0,0 +1,199 @@
+use crate::attr::{Nl80211ChanWidth, Nl80211DfsState, Nl80211FrequencyAttr};
+use crate::error::AttrError;
+use crate::parse_attr::parse_u32;
+use crate::types::{Mbm, Mhz};
+use neli::nlattr::AttrHandle;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// A channel of a band, with its regulatory restrictions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Channel {
+    /// Center frequency
+    pub frequency: Mhz,
+    /// Not allowed by the regulatory domain
+    pub disabled: bool,
+    /// Initiating radiation is forbidden: no beaconing, no probe requests
+    pub no_ir: bool,
+    /// Radar detection (DFS) is required
+    pub radar: bool,
+    /// Only allowed indoors
+    pub indoor_only: bool,
+    /// No 40 MHz channel with this one as primary and the secondary below
+    pub no_ht40_minus: bool,
+    /// No 40 MHz channel with this one as primary and the secondary above
+    pub no_ht40_plus: bool,
+    pub no_80mhz: bool,
+    pub no_160mhz: bool,
+    pub no_20mhz: bool,
+    pub no_10mhz: bool,
+    /// Maximum transmission power
+    pub max_tx_power: Option<Mbm>,
+    /// DFS state of a radar channel
+    pub dfs_state: Option<Nl80211DfsState>,
+    /// Time spent in the current DFS state
+    pub dfs_time: Option<Duration>,
+    /// Duration of the channel availability check (CAC) before using a radar channel
+    pub dfs_cac_time: Option<Duration>,
+}
+
+impl Channel {
+    /// Channel number, see [`Mhz::channel`](struct.Mhz.html#method.channel)
+    pub fn number(&self) -> Option<u32> {
+        self.frequency.channel()
+    }
+
+    /// The channel can be used with a width, as the primary channel
+    pub fn allows_width(&self, width: Nl80211ChanWidth) -> bool {
+        let ht40 = !self.no_ht40_minus || !self.no_ht40_plus;
+
+        match width {
+            Nl80211ChanWidth::ChanWidth20Noht | Nl80211ChanWidth::ChanWidth20 => !self.no_20mhz,
+            Nl80211ChanWidth::ChanWidth40 => !self.no_20mhz && ht40,
+            Nl80211ChanWidth::ChanWidth80 => !self.no_20mhz && ht40 && !self.no_80mhz,
+            Nl80211ChanWidth::ChanWidth80p80 | Nl80211ChanWidth::ChanWidth160 => {
+                !self.no_20mhz && ht40 && !self.no_80mhz && !self.no_160mhz
+            }
+            Nl80211ChanWidth::ChanWidth10 => !self.no_10mhz,
+            _ => true,
+        }
+    }
+
+    /// A radar channel needs a channel availability check before beaconing
+    pub fn needs_cac(&self) -> bool {
+        self.radar && self.dfs_state != Some(Nl80211DfsState::DfsAvailable)
+    }
+
+    /// An access point can be started on the channel with a width
+    ///
+    /// Radar channels are usable unless a radar was detected recently, they may need a channel
+    /// availability check first, see [`needs_cac`](#method.needs_cac). Their no IR flag is
+    /// ignored since the kernel lifts it once the check passed.
+    pub fn usable_for_ap(&self, width: Nl80211ChanWidth) -> bool {
+        !self.disabled
+            && (!self.no_ir || self.radar)
+            && self.dfs_state != Some(Nl80211DfsState::DfsUnavailable)
+            && self.allows_width(width)
+    }
+
+    pub(crate) fn parse(handle: AttrHandle<Nl80211FrequencyAttr>) -> Result<Channel, AttrError> {
+        let mut channel = Channel::default();
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211FrequencyAttr::FrequencyAttrFreq => {
+                    channel.frequency = Mhz(parse_u32(attr)?)
+                }
+                Nl80211FrequencyAttr::FrequencyAttrDisabled => channel.disabled = true,
+                Nl80211FrequencyAttr::FrequencyAttrNoIr => channel.no_ir = true,
+                Nl80211FrequencyAttr::FrequencyAttrRadar => channel.radar = true,
+                Nl80211FrequencyAttr::FrequencyAttrIndoorOnly => channel.indoor_only = true,
+                Nl80211FrequencyAttr::FrequencyAttrNoHt40Minus => channel.no_ht40_minus = true,
+                Nl80211FrequencyAttr::FrequencyAttrNoHt40Plus => channel.no_ht40_plus = true,
+                Nl80211FrequencyAttr::FrequencyAttrNo80mhz => channel.no_80mhz = true,
+                Nl80211FrequencyAttr::FrequencyAttrNo160mhz => channel.no_160mhz = true,
+                Nl80211FrequencyAttr::FrequencyAttrNo20mhz => channel.no_20mhz = true,
+                Nl80211FrequencyAttr::FrequencyAttrNo10mhz => channel.no_10mhz = true,
+                Nl80211FrequencyAttr::FrequencyAttrMaxTxPower => {
+                    channel.max_tx_power = Some(Mbm(parse_u32(attr)? as i32))
+                }
+                Nl80211FrequencyAttr::FrequencyAttrDfsState => {
+                    // Values too large for the enumeration are unknown, not truncated
+                    let state = u16::try_from(parse_u32(attr)?).unwrap_or(u16::MAX);
+                    channel.dfs_state = Some(Nl80211DfsState::from(state))
+                }
+                Nl80211FrequencyAttr::FrequencyAttrDfsTime => {
+                    channel.dfs_time = Some(Duration::from_millis(parse_u32(attr)? as u64))
+                }
+                Nl80211FrequencyAttr::FrequencyAttrDfsCacTime => {
+                    channel.dfs_cac_time = Some(Duration::from_millis(parse_u32(attr)? as u64))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(channel)
+    }
+}
+
+#[cfg(test)]
+mod test_channel {
+    use super::*;
+    use neli::nlattr::Nlattr;
+
+    fn attr(
+        nla_type: Nl80211FrequencyAttr,
+        payload: Vec<u8>,
+    ) -> Nlattr<Nl80211FrequencyAttr, Vec<u8>> {
+        Nlattr::new(None, nla_type, payload).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let handler = vec![
+            attr(Nl80211FrequencyAttr::FrequencyAttrFreq, vec![0x3c, 0x14, 0, 0]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrNoIr, vec![]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrRadar, vec![]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrMaxTxPower, vec![0xd0, 0x07, 0, 0]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrDfsState, vec![1, 0, 0, 0]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrDfsTime, vec![0x10, 0x27, 0, 0]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrDfsCacTime, vec![0x60, 0xea, 0, 0]),
+            attr(Nl80211FrequencyAttr::FrequencyAttrNo160mhz, vec![]),
+        ];
+
+        let channel = Channel::parse(AttrHandle::Owned(handler)).unwrap();
+
+        assert_eq!(channel.frequency, Mhz(5180));
+        assert_eq!(channel.number(), Some(36));
+        assert!(channel.no_ir && channel.radar && channel.no_160mhz);
+        assert!(!channel.disabled && !channel.indoor_only);
+        assert_eq!(channel.max_tx_power, Some(Mbm(2000)));
+        assert_eq!(channel.dfs_state, Some(Nl80211DfsState::DfsUnavailable));
+        assert_eq!(channel.dfs_time, Some(Duration::from_secs(10)));
+        assert_eq!(channel.dfs_cac_time, Some(Duration::from_secs(60)));
+
+        let handler = vec![attr(Nl80211FrequencyAttr::FrequencyAttrDfsTime, vec![1, 0])];
+        assert!(Channel::parse(AttrHandle::Owned(handler)).is_err());
+
+        let handler = vec![attr(Nl80211FrequencyAttr::FrequencyAttrDfsState, vec![2, 0, 1, 0])];
+        let channel = Channel::parse(AttrHandle::Owned(handler)).unwrap();
+        assert_eq!(channel.dfs_state, Some(Nl80211DfsState::UnrecognizedVariant(u16::MAX)));
+    }
+
+    #[test]
+    fn test_usable_for_ap() {
+        let channel = Channel {
+            frequency: Mhz(5260),
+            no_ir: true,
+            radar: true,
+            no_ht40_minus: true,
+            no_160mhz: true,
+            dfs_state: Some(Nl80211DfsState::DfsUsable),
+            ..Channel::default()
+        };
+
+        assert!(channel.usable_for_ap(Nl80211ChanWidth::ChanWidth20));
+        assert!(channel.usable_for_ap(Nl80211ChanWidth::ChanWidth80));
+        assert!(!channel.usable_for_ap(Nl80211ChanWidth::ChanWidth160));
+        assert!(channel.needs_cac());
+
+        let available = Channel {
+            dfs_state: Some(Nl80211DfsState::DfsAvailable),
+            ..channel.clone()
+        };
+        assert!(!available.needs_cac());
+        assert!(available.usable_for_ap(Nl80211ChanWidth::ChanWidth80));
+
+        let unavailable = Channel {
+            dfs_state: Some(Nl80211DfsState::DfsUnavailable),
+            ..channel.clone()
+        };
+        assert!(!unavailable.usable_for_ap(Nl80211ChanWidth::ChanWidth20));
+
+        let no_ir = Channel { radar: false, dfs_state: None, ..channel.clone() };
+        assert!(!no_ir.usable_for_ap(Nl80211ChanWidth::ChanWidth20));
+
+        let disabled = Channel { disabled: true, ..channel };
+        assert!(!disabled.usable_for_ap(Nl80211ChanWidth::ChanWidth20));
+    }
+}
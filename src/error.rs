@@ -0,0 +1,334 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+use neli::err::{DeError, NlError, SerError};
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors returned by the nl80211 socket
+#[derive(Debug)]
+pub enum Nl80211Error {
+    /// The kernel refused the command, usually because CAP_NET_ADMIN is missing (EPERM)
+    PermissionDenied(KernelError),
+    /// The interface or wiphy targeted by the command does not exist (ENODEV)
+    NoDevice(KernelError),
+    /// Any other error reported by the kernel
+    Kernel(KernelError),
+    /// A dump was interrupted by a change on the kernel side (NLM_F_DUMP_INTR or a new
+    /// generation) on every attempt
+    DumpInterrupted {
+        /// Command of the dump
+        cmd: Nl80211Cmd,
+        /// Number of attempts made
+        attempts: u32,
+    },
+    /// A scan was aborted before completion, by the driver or by
+    /// [`Socket::abort_scan`](struct.Socket.html#method.abort_scan)
+    ScanAborted {
+        /// Interface which was scanning
+        ifindex: u32,
+    },
+    /// The event ending a command wasn't received in time
+    Timeout {
+        /// Command waited for
+        cmd: Nl80211Cmd,
+    },
+    /// An attribute of a reply or an event could not be decoded
+    InvalidAttribute(AttrError),
+    /// Netlink socket or (de)serialization failure
+    Netlink(NlError),
+}
+
+/// An attribute whose payload doesn't have the length of the type it holds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttrError {
+    /// Name of the attribute, e.g. "StaInfoSignal"
+    pub attr: String,
+    /// Length of the payload in bytes
+    pub expected: usize,
+    /// Length of the payload received
+    pub actual: usize,
+}
+
+/// An error message (nlmsgerr) sent back by the kernel
+#[derive(Clone, Debug, PartialEq)]
+pub struct KernelError {
+    /// Positive errno value
+    pub errno: i32,
+    /// Command that failed
+    pub cmd: Nl80211Cmd,
+    /// Interface index the command was sent to
+    pub ifindex: Option<u32>,
+    /// Human readable message attached by the kernel (extended ACK)
+    pub message: Option<String>,
+    /// Top level attribute of the request the kernel rejected (extended ACK)
+    pub bad_attr: Option<Nl80211Attr>,
+}
+
+const NLMSGHDR_LEN: usize = 16;
+const GENLMSGHDR_LEN: usize = 4;
+const NLA_TYPE_MASK: u16 = 0x3fff;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NLMSGERR_ATTR_MSG: u16 = 1;
+const NLMSGERR_ATTR_OFFS: u16 = 2;
+
+/// Iterate over the (type, offset, payload) of netlink attributes packed in `bytes`
+fn nla_walk(bytes: &[u8]) -> impl Iterator<Item = (u16, usize, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let header = bytes.get(offset..offset + 4)?;
+        let nla_len = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let nla_type = u16::from_le_bytes([header[2], header[3]]) & NLA_TYPE_MASK;
+        if nla_len < 4 {
+            return None;
+        }
+        let payload = bytes.get(offset + 4..offset + nla_len)?;
+        let start = offset;
+        offset += (nla_len + 3) & !3;
+        Some((nla_type, start, payload))
+    })
+}
+
+impl Nl80211Error {
+    /// Decode the payload of a netlink error message
+    ///
+    /// The payload starts with a negative errno followed by the request that failed, from which the
+    /// failing command and interface index are recovered. When extended ACKs are enabled the kernel
+    /// appends TLVs after the request with a message and the offset of the offending attribute.
+    /// Returns `None` for acks (errno 0).
+    pub fn from_nlmsgerr(payload: &[u8]) -> Option<Nl80211Error> {
+        let errno = payload
+            .get(0..4)
+            .and_then(|errno| i32::from_le_bytes(errno.try_into().ok()?).checked_neg());
+        let errno = match errno {
+            Some(errno) => errno,
+            None => return Some(NlError::new("Malformed netlink error message").into()),
+        };
+        if errno == 0 {
+            return None;
+        }
+
+        let request = &payload[4..];
+        let request_len = request
+            .get(0..4)
+            .map(|len| u32::from_le_bytes(len.try_into().unwrap()) as usize)
+            .unwrap_or(0)
+            .min(request.len());
+
+        let cmd = request
+            .get(NLMSGHDR_LEN)
+            .map(|&cmd| Nl80211Cmd::from(cmd))
+            .unwrap_or(Nl80211Cmd::CmdUnspec);
+
+        let attrs_start = NLMSGHDR_LEN + GENLMSGHDR_LEN;
+        let attrs = request.get(attrs_start..request_len).unwrap_or(&[]);
+
+        let ifindex = nla_walk(attrs)
+            .find(|(nla_type, _, payload)| *nla_type == NL80211_ATTR_IFINDEX && payload.len() == 4)
+            .map(|(_, _, payload)| u32::from_le_bytes(payload.try_into().unwrap()));
+
+        let mut message = None;
+        let mut bad_attr = None;
+        let ack_tlvs = request.get((request_len + 3) & !3..).unwrap_or(&[]);
+        for (tlv_type, _, tlv) in nla_walk(ack_tlvs) {
+            match tlv_type {
+                NLMSGERR_ATTR_MSG => {
+                    let text = tlv.split(|&byte| byte == 0).next().unwrap_or(&[]);
+                    message = Some(String::from_utf8_lossy(text).to_string());
+                }
+                NLMSGERR_ATTR_OFFS if tlv.len() == 4 => {
+                    // The offset points into the echoed request, nested attributes are reported
+                    // through the top level attribute containing them
+                    let offset = u32::from_le_bytes(tlv.try_into().unwrap()) as usize;
+                    bad_attr = offset
+                        .checked_sub(attrs_start)
+                        .and_then(|offset| {
+                            nla_walk(attrs).find(|(_, start, payload)| {
+                                offset >= *start && offset < start + 4 + payload.len()
+                            })
+                        })
+                        .map(|(nla_type, _, _)| Nl80211Attr::from(nla_type));
+                }
+                _ => (),
+            }
+        }
+
+        let error = KernelError {
+            errno,
+            cmd,
+            ifindex,
+            message,
+            bad_attr,
+        };
+
+        Some(match errno {
+            libc::EPERM | libc::EACCES => Nl80211Error::PermissionDenied(error),
+            libc::ENODEV => Nl80211Error::NoDevice(error),
+            _ => Nl80211Error::Kernel(error),
+        })
+    }
+}
+
+impl fmt::Display for KernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} failed", self.cmd)?;
+        if let Some(ifindex) = self.ifindex {
+            write!(f, " on interface {}", ifindex)?;
+        }
+        write!(f, ": {}", io::Error::from_raw_os_error(self.errno))?;
+        if let Some(message) = &self.message {
+            write!(f, " ({})", message)?;
+        }
+        if let Some(bad_attr) = &self.bad_attr {
+            write!(f, ", offending attribute {}", bad_attr)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AttrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid attribute {}: expected {} bytes, got {}",
+            self.attr, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for AttrError {}
+
+impl fmt::Display for Nl80211Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Nl80211Error::PermissionDenied(error) => write!(f, "permission denied, {}", error),
+            Nl80211Error::NoDevice(error) => write!(f, "no such device, {}", error),
+            Nl80211Error::Kernel(error) => write!(f, "{}", error),
+            Nl80211Error::DumpInterrupted { cmd, attempts } => write!(
+                f,
+                "{:?} dump interrupted by kernel changes, gave up after {} attempts",
+                cmd, attempts
+            ),
+            Nl80211Error::ScanAborted { ifindex } => {
+                write!(f, "scan aborted on interface {}", ifindex)
+            }
+            Nl80211Error::Timeout { cmd } => write!(f, "{:?} timed out", cmd),
+            Nl80211Error::InvalidAttribute(error) => write!(f, "{}", error),
+            Nl80211Error::Netlink(error) => write!(f, "netlink error: {}", error),
+        }
+    }
+}
+
+impl Error for Nl80211Error {}
+
+impl From<NlError> for Nl80211Error {
+    fn from(error: NlError) -> Self {
+        Nl80211Error::Netlink(error)
+    }
+}
+
+impl From<AttrError> for Nl80211Error {
+    fn from(error: AttrError) -> Self {
+        Nl80211Error::InvalidAttribute(error)
+    }
+}
+
+impl From<io::Error> for Nl80211Error {
+    fn from(error: io::Error) -> Self {
+        Nl80211Error::Netlink(NlError::from(error))
+    }
+}
+
+impl From<SerError> for Nl80211Error {
+    fn from(error: SerError) -> Self {
+        Nl80211Error::Netlink(NlError::from(error))
+    }
+}
+
+impl From<DeError> for Nl80211Error {
+    fn from(error: DeError) -> Self {
+        Nl80211Error::Netlink(NlError::from(error))
+    }
+}
+
+#[cfg(test)]
+mod test_error {
+    use super::*;
+
+    fn nlmsgerr(errno: i32, cmd: u8, attrs: &[u8]) -> Vec<u8> {
+        let mut payload = (-errno).to_le_bytes().to_vec();
+        let len = (NLMSGHDR_LEN + GENLMSGHDR_LEN + attrs.len()) as u32;
+        payload.extend_from_slice(&len.to_le_bytes());
+        payload.extend_from_slice(&[28, 0, 5, 3, 1, 0, 0, 0, 0, 0, 0, 0]);
+        payload.extend_from_slice(&[cmd, 1, 0, 0]);
+        payload.extend_from_slice(attrs);
+        payload
+    }
+
+    #[test]
+    fn test_ack_is_not_an_error() {
+        assert!(Nl80211Error::from_nlmsgerr(&nlmsgerr(0, 17, &[])).is_none());
+    }
+
+    #[test]
+    fn test_malformed_nlmsgerr() {
+        for payload in &[vec![0, 0], i32::MIN.to_le_bytes().to_vec()] {
+            match Nl80211Error::from_nlmsgerr(payload) {
+                Some(Nl80211Error::Netlink(_)) => (),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_nlmsgerr() {
+        let payload = nlmsgerr(libc::ENODEV, 17, &[8, 0, 3, 0, 42, 0, 0, 0]);
+        match Nl80211Error::from_nlmsgerr(&payload) {
+            Some(Nl80211Error::NoDevice(error)) => assert_eq!(
+                error,
+                KernelError {
+                    errno: libc::ENODEV,
+                    cmd: Nl80211Cmd::CmdGetStation,
+                    ifindex: Some(42),
+                    message: None,
+                    bad_attr: None,
+                }
+            ),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let payload = nlmsgerr(libc::EPERM, 33, &[]);
+        match Nl80211Error::from_nlmsgerr(&payload) {
+            Some(Nl80211Error::PermissionDenied(error)) => {
+                assert_eq!(error.cmd, Nl80211Cmd::CmdTriggerScan);
+                assert_eq!(error.ifindex, None);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_extended_ack() {
+        // Request carrying AttrIfindex followed by AttrScanFlags
+        let mut payload = nlmsgerr(
+            libc::EINVAL,
+            33,
+            &[8, 0, 3, 0, 3, 0, 0, 0, 8, 0, 158, 0, 255, 0, 0, 0],
+        );
+        // NLMSGERR_ATTR_MSG
+        payload.extend_from_slice(&[16, 0, 1, 0]);
+        payload.extend_from_slice(b"bad flags\0\0\0");
+        // NLMSGERR_ATTR_OFFS, pointing to the second attribute of the request
+        payload.extend_from_slice(&[8, 0, 2, 0, 28, 0, 0, 0]);
+
+        match Nl80211Error::from_nlmsgerr(&payload) {
+            Some(Nl80211Error::Kernel(error)) => {
+                assert_eq!(error.ifindex, Some(3));
+                assert_eq!(error.message, Some("bad flags".to_string()));
+                assert_eq!(error.bad_attr, Some(Nl80211Attr::AttrScanFlags));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+use crate::consts::*;
+use crate::error::Nl80211Error;
+use crate::interface::Interface;
+use crate::nl80211traits::ParseNlAttr;
+use crate::parse_attr::{fixed_payload, parse_string, parse_u16, parse_u32, parse_u8};
+use crate::types::MacAddr;
+use neli::consts::NlFamily;
+use neli::genl::Genlmsghdr;
+use neli::socket::NlSocket;
+use std::convert::TryInto;
+use std::fmt;
+
+/// Multicast groups on which nl80211 publishes events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MulticastGroup {
+    /// Wiphy and interface creation, deletion and renaming
+    Config,
+    /// Scan triggered, done or aborted, scheduled scan results
+    Scan,
+    /// Regulatory domain changes
+    Regulatory,
+    /// Authentication, association, connection and roaming
+    Mlme,
+    /// Vendor specific events
+    Vendor,
+    /// Neighbor Awareness Networking
+    Nan,
+}
+
+impl MulticastGroup {
+    /// Name of the group as registered by nl80211 to the generic netlink controller
+    pub fn name(&self) -> &'static str {
+        match self {
+            MulticastGroup::Config => NL_80211_MULTICAST_GROUP_CONFIG,
+            MulticastGroup::Scan => NL_80211_MULTICAST_GROUP_SCAN,
+            MulticastGroup::Regulatory => NL_80211_MULTICAST_GROUP_REG,
+            MulticastGroup::Mlme => NL_80211_MULTICAST_GROUP_MLME,
+            MulticastGroup::Vendor => NL_80211_MULTICAST_GROUP_VENDOR,
+            MulticastGroup::Nan => NL_80211_MULTICAST_GROUP_NAN,
+        }
+    }
+
+    /// Ask the generic netlink controller for the id of this group
+    pub fn resolve(&self) -> Result<u32, Nl80211Error> {
+        let id = NlSocket::new(NlFamily::Generic, true)?
+            .resolve_nl_mcast_group(NL_80211_GENL_NAME, self.name())?;
+        Ok(id)
+    }
+}
+
+impl fmt::Display for MulticastGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// An event published by nl80211 on a multicast group
+#[derive(Clone, Debug, PartialEq)]
+pub enum Nl80211Event {
+    /// A scan finished, results can be fetched with CmdGetScan
+    ScanDone {
+        wiphy: Option<u32>,
+        ifindex: Option<u32>,
+    },
+    /// A scan was aborted
+    ScanAborted {
+        wiphy: Option<u32>,
+        ifindex: Option<u32>,
+    },
+    /// A scheduled scan found BSS matching its match sets, results can be fetched with CmdGetScan
+    SchedScanResults {
+        wiphy: Option<u32>,
+        ifindex: Option<u32>,
+    },
+    /// A scheduled scan stopped, on request or because the driver gave up
+    SchedScanStopped {
+        wiphy: Option<u32>,
+        ifindex: Option<u32>,
+    },
+    /// Result of a connection attempt, status 0 means success
+    Connect {
+        ifindex: Option<u32>,
+        bssid: Option<MacAddr>,
+        status: Option<u16>,
+    },
+    /// The interface was disconnected
+    Disconnect {
+        ifindex: Option<u32>,
+        reason: Option<u16>,
+        /// The disconnection was initiated by the access point
+        by_ap: bool,
+    },
+    /// The interface roamed to another access point
+    Roam {
+        ifindex: Option<u32>,
+        bssid: Option<MacAddr>,
+    },
+    /// An interface was created
+    NewInterface(Interface),
+    /// An interface was deleted
+    DelInterface(Interface),
+    /// The regulatory domain changed
+    RegChange {
+        wiphy: Option<u32>,
+        /// Who asked for the change (core, user, driver, country IE)
+        initiator: Option<u8>,
+        /// Type of regulatory domain (country, world, custom world, intersection)
+        reg_type: Option<u8>,
+        /// ISO-3166 country code
+        alpha2: Option<String>,
+    },
+    /// Any other command sent by nl80211
+    Other {
+        cmd: Nl80211Cmd,
+        ifindex: Option<u32>,
+    },
+}
+
+impl Nl80211Event {
+    /// Build an event from a message received on a multicast group
+    ///
+    /// Fails when one of the attributes can't be decoded.
+    pub fn from_genlmsghdr(
+        genlhdr: &Genlmsghdr<Nl80211Cmd, Nl80211Attr>,
+    ) -> Result<Nl80211Event, Nl80211Error> {
+        let handle = genlhdr.get_attr_handle();
+
+        let mut wiphy = None;
+        let mut ifindex = None;
+        let mut bssid = None;
+        let mut status = None;
+        let mut reason = None;
+        let mut by_ap = false;
+        let mut initiator = None;
+        let mut reg_type = None;
+        let mut alpha2 = None;
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211Attr::AttrWiphy => wiphy = Some(parse_u32(attr)?),
+                Nl80211Attr::AttrIfindex => ifindex = Some(parse_u32(attr)?),
+                Nl80211Attr::AttrMac => {
+                    bssid = Some(MacAddr(fixed_payload(attr, 6)?.try_into().unwrap()))
+                }
+                Nl80211Attr::AttrStatusCode => status = Some(parse_u16(attr)?),
+                Nl80211Attr::AttrReasonCode => reason = Some(parse_u16(attr)?),
+                Nl80211Attr::AttrDisconnectedByAp => by_ap = true,
+                Nl80211Attr::AttrRegInitiator => initiator = Some(parse_u8(attr)?),
+                Nl80211Attr::AttrRegType => reg_type = Some(parse_u8(attr)?),
+                Nl80211Attr::AttrRegAlpha2 => {
+                    alpha2 = Some(parse_string(&attr.payload).trim_end_matches('\0').to_string())
+                }
+                _ => (),
+            }
+        }
+
+        let event = match &genlhdr.cmd {
+            Nl80211Cmd::CmdNewScanResults => Nl80211Event::ScanDone { wiphy, ifindex },
+            Nl80211Cmd::CmdScanAborted => Nl80211Event::ScanAborted { wiphy, ifindex },
+            Nl80211Cmd::CmdSchedScanResults => Nl80211Event::SchedScanResults { wiphy, ifindex },
+            Nl80211Cmd::CmdSchedScanStopped => Nl80211Event::SchedScanStopped { wiphy, ifindex },
+            Nl80211Cmd::CmdConnect => Nl80211Event::Connect {
+                ifindex,
+                bssid,
+                status,
+            },
+            Nl80211Cmd::CmdDisconnect => Nl80211Event::Disconnect {
+                ifindex,
+                reason,
+                by_ap,
+            },
+            Nl80211Cmd::CmdRoam => Nl80211Event::Roam { ifindex, bssid },
+            Nl80211Cmd::CmdNewInterface => {
+                Nl80211Event::NewInterface(Interface::default().parse(genlhdr.get_attr_handle())?)
+            }
+            Nl80211Cmd::CmdDelInterface => {
+                Nl80211Event::DelInterface(Interface::default().parse(genlhdr.get_attr_handle())?)
+            }
+            Nl80211Cmd::CmdRegChange | Nl80211Cmd::CmdWiphyRegChange => Nl80211Event::RegChange {
+                wiphy,
+                initiator,
+                reg_type,
+                alpha2,
+            },
+            cmd => Nl80211Event::Other {
+                cmd: cmd.clone(),
+                ifindex,
+            },
+        };
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod test_event {
+    use super::*;
+    use neli::nlattr::Nlattr;
+
+    fn event(cmd: Nl80211Cmd, attrs: Vec<(Nl80211Attr, Vec<u8>)>) -> Nl80211Event {
+        let attrs = attrs
+            .into_iter()
+            .map(|(attr, payload)| Nlattr::new(None, attr, payload).unwrap())
+            .collect();
+        let genlhdr = Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs).unwrap();
+        Nl80211Event::from_genlmsghdr(&genlhdr).unwrap()
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let disconnect = event(
+            Nl80211Cmd::CmdDisconnect,
+            vec![
+                (Nl80211Attr::AttrIfindex, vec![3, 0, 0, 0]),
+                (Nl80211Attr::AttrReasonCode, vec![3, 0]),
+                (Nl80211Attr::AttrDisconnectedByAp, vec![]),
+            ],
+        );
+
+        assert_eq!(
+            disconnect,
+            Nl80211Event::Disconnect {
+                ifindex: Some(3),
+                reason: Some(3),
+                by_ap: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_connect() {
+        let connect = event(
+            Nl80211Cmd::CmdConnect,
+            vec![
+                (Nl80211Attr::AttrIfindex, vec![3, 0, 0, 0]),
+                (Nl80211Attr::AttrMac, vec![0x50, 0x3e, 0xaa, 0x1b, 0x2c, 0x3d]),
+                (Nl80211Attr::AttrStatusCode, vec![0, 0]),
+            ],
+        );
+
+        assert_eq!(
+            connect,
+            Nl80211Event::Connect {
+                ifindex: Some(3),
+                bssid: Some(MacAddr([0x50, 0x3e, 0xaa, 0x1b, 0x2c, 0x3d])),
+                status: Some(0)
+            }
+        );
+
+        let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrMac, vec![0x50, 0x3e]).unwrap()];
+        let genlhdr = Genlmsghdr::new(Nl80211Cmd::CmdConnect, NL_80211_GENL_VERSION, attrs).unwrap();
+        match Nl80211Event::from_genlmsghdr(&genlhdr) {
+            Err(Nl80211Error::InvalidAttribute(err)) => assert_eq!(err.actual, 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sched_scan() {
+        let attrs = vec![
+            (Nl80211Attr::AttrWiphy, vec![0, 0, 0, 0]),
+            (Nl80211Attr::AttrIfindex, vec![3, 0, 0, 0]),
+        ];
+
+        assert_eq!(
+            event(Nl80211Cmd::CmdSchedScanResults, attrs.clone()),
+            Nl80211Event::SchedScanResults {
+                wiphy: Some(0),
+                ifindex: Some(3)
+            }
+        );
+        assert_eq!(
+            event(Nl80211Cmd::CmdSchedScanStopped, attrs),
+            Nl80211Event::SchedScanStopped {
+                wiphy: Some(0),
+                ifindex: Some(3)
+            }
+        );
+    }
+
+    #[test]
+    fn test_reg_change() {
+        let reg_change = event(
+            Nl80211Cmd::CmdRegChange,
+            vec![
+                (Nl80211Attr::AttrRegInitiator, vec![1]),
+                (Nl80211Attr::AttrRegType, vec![0]),
+                (Nl80211Attr::AttrRegAlpha2, b"FR\0".to_vec()),
+            ],
+        );
+
+        assert_eq!(
+            reg_change,
+            Nl80211Event::RegChange {
+                wiphy: None,
+                initiator: Some(1),
+                reg_type: Some(0),
+                alpha2: Some("FR".to_string())
+            }
+        );
+    }
+}
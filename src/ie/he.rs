@@ -0,0 +1,183 @@
+use crate::ie::le_u16;
+
+/// HE Capabilities extension element (802.11ax)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeCapabilities {
+    pub mac_cap: [u8; 6],
+    pub phy_cap: [u8; 11],
+    /// Supported HE-MCS and NSS set: RX and TX maps for 80 MHz, then for 160 and 80+80 MHz when
+    /// supported
+    pub mcs_nss: Vec<u8>,
+}
+
+impl HeCapabilities {
+    pub(crate) fn parse(data: &[u8]) -> Option<HeCapabilities> {
+        let mut mac_cap = [0; 6];
+        mac_cap.copy_from_slice(data.get(0..6)?);
+        let mut phy_cap = [0; 11];
+        phy_cap.copy_from_slice(data.get(6..17)?);
+        let mcs_nss = data.get(17..21)?;
+
+        Some(HeCapabilities {
+            mac_cap,
+            phy_cap,
+            mcs_nss: [mcs_nss, &data[21..]].concat(),
+        })
+    }
+
+    /// 40 MHz channels are supported in the 2.4 GHz band
+    pub fn supports_40mhz_in_2ghz(&self) -> bool {
+        self.phy_cap[0] & (1 << 1) != 0
+    }
+
+    /// 40 and 80 MHz channels are supported in the 5 and 6 GHz bands
+    pub fn supports_80mhz_in_5ghz(&self) -> bool {
+        self.phy_cap[0] & (1 << 2) != 0
+    }
+
+    /// 160 MHz channels are supported in the 5 and 6 GHz bands
+    pub fn supports_160mhz_in_5ghz(&self) -> bool {
+        self.phy_cap[0] & (1 << 3) != 0
+    }
+
+    /// 80+80 MHz channels are supported in the 5 and 6 GHz bands
+    pub fn supports_80p80mhz_in_5ghz(&self) -> bool {
+        self.phy_cap[0] & (1 << 4) != 0
+    }
+
+    /// HE-MCS map for reception on channels up to 80 MHz, two bits per spatial stream: 0 for
+    /// MCS 0-7, 1 for MCS 0-9, 2 for MCS 0-11, 3 if unsupported
+    pub fn rx_mcs_map_80(&self) -> u16 {
+        le_u16(&self.mcs_nss, 0).unwrap_or(0xffff)
+    }
+
+    /// Spatial streams supported for reception on channels up to 80 MHz
+    pub fn rx_spatial_streams(&self) -> u8 {
+        let mcs_map = self.rx_mcs_map_80();
+        (0..8).filter(|nss| (mcs_map >> (nss * 2)) & 0x3 != 0x3).count() as u8
+    }
+}
+
+/// HE Operation extension element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeOperation {
+    /// HE operation parameters, 3 bytes of bit fields
+    pub params: u32,
+    /// BSS color (bits 0-5), partial BSS color (bit 6) and BSS color disabled (bit 7)
+    pub bss_color_info: u8,
+    pub basic_mcs_nss: u16,
+    /// VHT operation information: channel width and center frequency segments
+    pub vht_operation: Option<[u8; 3]>,
+    pub max_cohosted_bssid_indicator: Option<u8>,
+    pub six_ghz_operation: Option<SixGhzOperation>,
+}
+
+/// 6 GHz operation information of an HE Operation element
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SixGhzOperation {
+    pub primary_channel: u8,
+    /// Channel width (bits 0-1), duplicate beacon (bit 2) and regulatory info (bits 3-5)
+    pub control: u8,
+    pub center_freq_seg0: u8,
+    pub center_freq_seg1: u8,
+    /// Minimum rate, in units of 1 MBit/s
+    pub min_rate: u8,
+}
+
+impl HeOperation {
+    const VHT_OPERATION_PRESENT: u32 = 1 << 14;
+    const COHOSTED_BSS: u32 = 1 << 15;
+    const SIX_GHZ_OPERATION_PRESENT: u32 = 1 << 17;
+
+    pub(crate) fn parse(data: &[u8]) -> Option<HeOperation> {
+        let params = data.get(0..3)?;
+        let params = params[0] as u32 | (params[1] as u32) << 8 | (params[2] as u32) << 16;
+        let mut he_operation = HeOperation {
+            params,
+            bss_color_info: *data.get(3)?,
+            basic_mcs_nss: le_u16(data, 4)?,
+            vht_operation: None,
+            max_cohosted_bssid_indicator: None,
+            six_ghz_operation: None,
+        };
+
+        let mut offset = 6;
+        if params & HeOperation::VHT_OPERATION_PRESENT != 0 {
+            let mut vht_operation = [0; 3];
+            vht_operation.copy_from_slice(data.get(offset..offset + 3)?);
+            he_operation.vht_operation = Some(vht_operation);
+            offset += 3;
+        }
+        if params & HeOperation::COHOSTED_BSS != 0 {
+            he_operation.max_cohosted_bssid_indicator = Some(*data.get(offset)?);
+            offset += 1;
+        }
+        if params & HeOperation::SIX_GHZ_OPERATION_PRESENT != 0 {
+            let six_ghz = data.get(offset..offset + 5)?;
+            he_operation.six_ghz_operation = Some(SixGhzOperation {
+                primary_channel: six_ghz[0],
+                control: six_ghz[1],
+                center_freq_seg0: six_ghz[2],
+                center_freq_seg1: six_ghz[3],
+                min_rate: six_ghz[4],
+            });
+        }
+
+        Some(he_operation)
+    }
+
+    /// BSS color, used to tell apart overlapping BSS
+    pub fn bss_color(&self) -> u8 {
+        self.bss_color_info & 0x3f
+    }
+
+    /// BSS coloring is disabled
+    pub fn bss_color_disabled(&self) -> bool {
+        self.bss_color_info & (1 << 7) != 0
+    }
+}
+
+#[cfg(test)]
+mod test_he {
+    use super::*;
+
+    #[test]
+    fn test_he_capabilities() {
+        let data = vec![
+            0x09, 0x0d, 0x08, 0x12, 0x00, 0x10, 0x22, 0x20, 0x02, 0xc0, 0x0f, 0x03, 0x95, 0x18,
+            0x00, 0xcc, 0x00, 0xfa, 0xff, 0xfa, 0xff, 0x3b, 0x1c, 0xc7, 0x71,
+        ];
+        let he_cap = HeCapabilities::parse(&data).unwrap();
+
+        assert!(he_cap.supports_40mhz_in_2ghz());
+        assert!(!he_cap.supports_80mhz_in_5ghz());
+        assert_eq!(he_cap.rx_mcs_map_80(), 0xfffa);
+        assert_eq!(he_cap.rx_spatial_streams(), 2);
+        assert_eq!(he_cap.mcs_nss.len(), 8);
+        assert_eq!(HeCapabilities::parse(&data[..20]), None);
+    }
+
+    #[test]
+    fn test_he_operation() {
+        let data = vec![
+            0x04, 0x40, 0x02, 0x0b, 0xfc, 0xff, 0x49, 0x01, 0x07, 0x25, 0x02, 0x07, 0x00, 0x01,
+        ];
+        let he_op = HeOperation::parse(&data).unwrap();
+
+        assert_eq!(he_op.bss_color(), 11);
+        assert!(!he_op.bss_color_disabled());
+        assert_eq!(he_op.vht_operation, Some([0x49, 0x01, 0x07]));
+        assert_eq!(he_op.max_cohosted_bssid_indicator, None);
+        assert_eq!(
+            he_op.six_ghz_operation,
+            Some(SixGhzOperation {
+                primary_channel: 0x25,
+                control: 0x02,
+                center_freq_seg0: 0x07,
+                center_freq_seg1: 0x00,
+                min_rate: 0x01,
+            })
+        );
+        assert_eq!(HeOperation::parse(&data[..13]), None);
+    }
+}
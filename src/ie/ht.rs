@@ -0,0 +1,264 @@
+use crate::ie::{le_u16, le_u32};
+use crate::rate_info::RateWidth;
+
+/// HT Capabilities element (802.11n)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtCapabilities {
+    pub cap_info: u16,
+    /// Maximum A-MPDU length exponent (bits 0-1) and minimum MPDU start spacing (bits 2-4)
+    pub ampdu_params: u8,
+    /// Supported MCS set
+    pub mcs_set: [u8; 16],
+    pub extended_cap: u16,
+    pub txbf_cap: u32,
+    pub asel_cap: u8,
+}
+
+impl HtCapabilities {
+    pub(crate) fn parse(data: &[u8]) -> Option<HtCapabilities> {
+        let mut mcs_set = [0; 16];
+        mcs_set.copy_from_slice(data.get(3..19)?);
+
+        Some(HtCapabilities {
+            cap_info: le_u16(data, 0)?,
+            ampdu_params: *data.get(2)?,
+            mcs_set,
+            extended_cap: le_u16(data, 19)?,
+            txbf_cap: le_u32(data, 21)?,
+            asel_cap: *data.get(25)?,
+        })
+    }
+
+    /// LDPC coding is supported
+    pub fn ldpc(&self) -> bool {
+        self.cap_info & (1 << 0) != 0
+    }
+
+    /// 40 MHz channels are supported
+    pub fn supports_40mhz(&self) -> bool {
+        self.cap_info & (1 << 1) != 0
+    }
+
+    /// Short guard interval is supported for 20 MHz channels
+    pub fn short_gi_20(&self) -> bool {
+        self.cap_info & (1 << 5) != 0
+    }
+
+    /// Short guard interval is supported for 40 MHz channels
+    pub fn short_gi_40(&self) -> bool {
+        self.cap_info & (1 << 6) != 0
+    }
+
+    /// Maximum length of a received A-MPDU, in bytes
+    pub fn max_ampdu_length(&self) -> u32 {
+        (1 << (13 + (self.ampdu_params & 0x3))) - 1
+    }
+
+    /// MCS indexes supported for reception
+    pub fn rx_mcs(&self) -> Vec<u8> {
+        (0..77)
+            .filter(|mcs| self.mcs_set[mcs / 8] & (1 << (mcs % 8)) != 0)
+            .map(|mcs| mcs as u8)
+            .collect()
+    }
+
+    /// Spatial streams supported for reception, from the equal modulation MCS 0 to 31
+    pub fn rx_spatial_streams(&self) -> u8 {
+        self.mcs_set[..4].iter().filter(|streams| **streams != 0).count() as u8
+    }
+}
+
+/// HT Operation element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtOperation {
+    pub primary_channel: u8,
+    /// HT operation information, 5 bytes of bit fields
+    pub info: [u8; 5],
+    /// MCS supported by every station of the BSS
+    pub basic_mcs_set: [u8; 16],
+}
+
+impl HtOperation {
+    pub(crate) fn parse(data: &[u8]) -> Option<HtOperation> {
+        let mut info = [0; 5];
+        info.copy_from_slice(data.get(1..6)?);
+        let mut basic_mcs_set = [0; 16];
+        basic_mcs_set.copy_from_slice(data.get(6..22)?);
+
+        Some(HtOperation {
+            primary_channel: data[0],
+            info,
+            basic_mcs_set,
+        })
+    }
+
+    /// Offset of the secondary channel: 0 for none, 1 above the primary channel, 3 below
+    pub fn secondary_channel_offset(&self) -> u8 {
+        self.info[0] & 0x3
+    }
+
+    /// Any channel width is allowed, 20 MHz only otherwise
+    pub fn sta_channel_width_any(&self) -> bool {
+        self.info[0] & (1 << 2) != 0
+    }
+
+    /// HT protection mode, 0 for no protection to 3 for HT mixed mode
+    pub fn ht_protection(&self) -> u8 {
+        self.info[1] & 0x3
+    }
+}
+
+/// VHT Capabilities element (802.11ac)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VhtCapabilities {
+    pub cap_info: u32,
+    /// Two bits per spatial stream: 0 for MCS 0-7, 1 for MCS 0-8, 2 for MCS 0-9, 3 if unsupported
+    pub rx_mcs_map: u16,
+    /// Highest supported long GI data rate for reception, in MBit/s
+    pub rx_highest: u16,
+    pub tx_mcs_map: u16,
+    pub tx_highest: u16,
+}
+
+impl VhtCapabilities {
+    pub(crate) fn parse(data: &[u8]) -> Option<VhtCapabilities> {
+        Some(VhtCapabilities {
+            cap_info: le_u32(data, 0)?,
+            rx_mcs_map: le_u16(data, 4)?,
+            rx_highest: le_u16(data, 6)? & 0x1fff,
+            tx_mcs_map: le_u16(data, 8)?,
+            tx_highest: le_u16(data, 10)? & 0x1fff,
+        })
+    }
+
+    /// Supported channel width set: 0 for 80 MHz, 1 for 160 MHz, 2 for 160 and 80+80 MHz
+    pub fn supported_channel_width(&self) -> u8 {
+        ((self.cap_info >> 2) & 0x3) as u8
+    }
+
+    /// Short guard interval is supported for 80 MHz channels
+    pub fn short_gi_80(&self) -> bool {
+        self.cap_info & (1 << 5) != 0
+    }
+
+    /// Short guard interval is supported for 160 and 80+80 MHz channels
+    pub fn short_gi_160(&self) -> bool {
+        self.cap_info & (1 << 6) != 0
+    }
+
+    /// Single user beamformer
+    pub fn su_beamformer(&self) -> bool {
+        self.cap_info & (1 << 11) != 0
+    }
+
+    /// Multi user beamformer
+    pub fn mu_beamformer(&self) -> bool {
+        self.cap_info & (1 << 19) != 0
+    }
+
+    /// Highest MCS index received with `nss` spatial streams (1 to 8)
+    pub fn rx_max_mcs(&self, nss: u8) -> Option<u8> {
+        vht_max_mcs(self.rx_mcs_map, nss)
+    }
+
+    /// Spatial streams supported for reception
+    pub fn rx_spatial_streams(&self) -> u8 {
+        (1..=8)
+            .filter(|nss| self.rx_max_mcs(*nss).is_some())
+            .count() as u8
+    }
+}
+
+/// Highest MCS index of a VHT MCS map for `nss` spatial streams
+pub(crate) fn vht_max_mcs(mcs_map: u16, nss: u8) -> Option<u8> {
+    if nss == 0 || nss > 8 {
+        return None;
+    }
+
+    match (mcs_map >> ((nss - 1) * 2)) & 0x3 {
+        0 => Some(7),
+        1 => Some(8),
+        2 => Some(9),
+        _ => None,
+    }
+}
+
+/// VHT Operation element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VhtOperation {
+    /// 0 for 20 or 40 MHz, as set by the HT Operation element, 1 for 80, 160 or 80+80 MHz
+    pub channel_width: u8,
+    /// Channel number of the center of the channel, or of the primary 80 MHz segment
+    pub center_freq_seg0: u8,
+    /// Channel number of the center of the 160 MHz channel, or of the secondary 80 MHz segment
+    pub center_freq_seg1: u8,
+    pub basic_mcs_map: u16,
+}
+
+impl VhtOperation {
+    pub(crate) fn parse(data: &[u8]) -> Option<VhtOperation> {
+        Some(VhtOperation {
+            channel_width: *data.first()?,
+            center_freq_seg0: *data.get(1)?,
+            center_freq_seg1: *data.get(2)?,
+            basic_mcs_map: le_u16(data, 3)?,
+        })
+    }
+
+    /// Width of the operating channel, `None` when it is set by the HT Operation element
+    pub fn width(&self) -> Option<RateWidth> {
+        let distance = (self.center_freq_seg0 as i16 - self.center_freq_seg1 as i16).abs();
+        match self.channel_width {
+            1 if self.center_freq_seg1 == 0 => Some(RateWidth::Mhz80),
+            1 if distance == 8 => Some(RateWidth::Mhz160),
+            1 => Some(RateWidth::Mhz80p80),
+            // Deprecated encodings of 160 and 80+80 MHz channels
+            2 => Some(RateWidth::Mhz160),
+            3 => Some(RateWidth::Mhz80p80),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ht {
+    use super::*;
+
+    #[test]
+    fn test_ht_capabilities() {
+        let data = vec![
+            188, 9, 27, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let ht_cap = HtCapabilities::parse(&data).unwrap();
+
+        assert_eq!(ht_cap.cap_info, 0x09bc);
+        assert!(!ht_cap.ldpc());
+        assert!(!ht_cap.supports_40mhz());
+        assert!(ht_cap.short_gi_20());
+        assert_eq!(ht_cap.max_ampdu_length(), 65535);
+        assert_eq!(ht_cap.rx_spatial_streams(), 2);
+        assert_eq!(ht_cap.rx_mcs(), (0..16).collect::<Vec<u8>>());
+        assert_eq!(HtCapabilities::parse(&data[..25]), None);
+    }
+
+    #[test]
+    fn test_vht() {
+        let vht_cap = VhtCapabilities::parse(&[
+            0xb2, 0x01, 0x80, 0x33, 0xfa, 0xff, 0x0c, 0x03, 0xfa, 0xff, 0x0c, 0x03,
+        ])
+        .unwrap();
+
+        assert_eq!(vht_cap.supported_channel_width(), 0);
+        assert!(vht_cap.short_gi_80());
+        assert_eq!(vht_cap.rx_max_mcs(1), Some(9));
+        assert_eq!(vht_cap.rx_max_mcs(3), None);
+        assert_eq!(vht_cap.rx_spatial_streams(), 2);
+        assert_eq!(vht_cap.rx_highest, 780);
+
+        let vht_op = VhtOperation::parse(&[1, 42, 0, 0xfc, 0xff]).unwrap();
+        assert_eq!(vht_op.width(), Some(RateWidth::Mhz80));
+        let vht_op = VhtOperation::parse(&[1, 42, 50, 0xfc, 0xff]).unwrap();
+        assert_eq!(vht_op.width(), Some(RateWidth::Mhz160));
+        assert_eq!(VhtOperation::parse(&[1, 42, 50]), None);
+    }
+}
@@ -0,0 +1,493 @@
+//! 802.11 information elements
+//!
+//! Beacons and probe responses describe a BSS with a list of information elements (IE), each one
+//! made of an ID, a length and a body. Scan results carry them in `BssInformationElements` and
+//! `BssBeaconIes`.
+//!
+//! ```
+//! use nl80211::ie::{Element, Elements};
+//!
+//! let ies = [0, 4, 0x74, 0x65, 0x73, 0x74, 3, 1, 6];
+//! for element in Elements::new(&ies) {
+//!     if let Element::DsParameter(channel) = element {
+//!         assert_eq!(channel, 6);
+//!     }
+//! }
+//! ```
+
+use crate::types::{Dbm, Ssid};
+use std::convert::TryInto;
+use std::fmt;
+
+mod ht;
+pub use ht::*;
+mod he;
+pub use he::*;
+mod rsn;
+pub use rsn::*;
+
+const ID_SSID: u8 = 0;
+const ID_SUPPORTED_RATES: u8 = 1;
+const ID_DS_PARAMETER: u8 = 3;
+const ID_TIM: u8 = 5;
+const ID_COUNTRY: u8 = 7;
+const ID_BSS_LOAD: u8 = 11;
+const ID_HT_CAPABILITIES: u8 = 45;
+const ID_RSN: u8 = 48;
+const ID_EXTENDED_RATES: u8 = 50;
+const ID_MOBILITY_DOMAIN: u8 = 54;
+const ID_HT_OPERATION: u8 = 61;
+const ID_EXTENDED_CAPABILITIES: u8 = 127;
+const ID_VHT_CAPABILITIES: u8 = 191;
+const ID_VHT_OPERATION: u8 = 192;
+const ID_VENDOR: u8 = 221;
+const ID_EXTENSION: u8 = 255;
+
+const EXT_ID_HE_CAPABILITIES: u8 = 35;
+const EXT_ID_HE_OPERATION: u8 = 36;
+
+/// A decoded information element
+#[derive(Clone, Debug, PartialEq)]
+pub enum Element {
+    Ssid(Ssid),
+    SupportedRates(Vec<Rate>),
+    /// Current channel
+    DsParameter(u8),
+    Tim(Tim),
+    Country(Country),
+    BssLoad(BssLoad),
+    HtCapabilities(HtCapabilities),
+    HtOperation(HtOperation),
+    ExtendedRates(Vec<Rate>),
+    Rsn(Rsn),
+    MobilityDomain(MobilityDomain),
+    ExtendedCapabilities(ExtendedCapabilities),
+    VhtCapabilities(VhtCapabilities),
+    VhtOperation(VhtOperation),
+    HeCapabilities(HeCapabilities),
+    HeOperation(HeOperation),
+    Vendor(Vendor),
+    /// Extension element (ID 255) this crate doesn't decode
+    Extension { ext_id: u8, data: Vec<u8> },
+    /// Element this crate doesn't decode, or too short to be decoded
+    Unknown { id: u8, data: Vec<u8> },
+}
+
+impl Element {
+    /// Decode the body of an element
+    pub fn parse(id: u8, data: &[u8]) -> Element {
+        let element = match id {
+            ID_SSID if data.len() <= 32 => Some(Element::Ssid(Ssid(data.to_vec()))),
+            ID_SUPPORTED_RATES => Some(Element::SupportedRates(Rate::parse_all(data))),
+            ID_DS_PARAMETER => data.first().map(|channel| Element::DsParameter(*channel)),
+            ID_TIM => Tim::parse(data).map(Element::Tim),
+            ID_COUNTRY => Country::parse(data).map(Element::Country),
+            ID_BSS_LOAD => BssLoad::parse(data).map(Element::BssLoad),
+            ID_HT_CAPABILITIES => HtCapabilities::parse(data).map(Element::HtCapabilities),
+            ID_HT_OPERATION => HtOperation::parse(data).map(Element::HtOperation),
+            ID_EXTENDED_RATES => Some(Element::ExtendedRates(Rate::parse_all(data))),
+            ID_RSN => Rsn::parse(data).map(Element::Rsn),
+            ID_MOBILITY_DOMAIN => MobilityDomain::parse(data).map(Element::MobilityDomain),
+            ID_EXTENDED_CAPABILITIES => {
+                Some(Element::ExtendedCapabilities(ExtendedCapabilities(data.to_vec())))
+            }
+            ID_VHT_CAPABILITIES => VhtCapabilities::parse(data).map(Element::VhtCapabilities),
+            ID_VHT_OPERATION => VhtOperation::parse(data).map(Element::VhtOperation),
+            ID_VENDOR => Vendor::parse(data).map(Element::Vendor),
+            ID_EXTENSION => Element::parse_extension(data),
+            _ => None,
+        };
+
+        element.unwrap_or_else(|| Element::Unknown {
+            id,
+            data: data.to_vec(),
+        })
+    }
+
+    fn parse_extension(data: &[u8]) -> Option<Element> {
+        let (ext_id, data) = data.split_first()?;
+        match *ext_id {
+            EXT_ID_HE_CAPABILITIES => HeCapabilities::parse(data).map(Element::HeCapabilities),
+            EXT_ID_HE_OPERATION => HeOperation::parse(data).map(Element::HeOperation),
+            _ => Some(Element::Extension {
+                ext_id: *ext_id,
+                data: data.to_vec(),
+            }),
+        }
+    }
+}
+
+/// Iterator over the information elements of a beacon or probe response
+///
+/// The iteration stops at the first truncated element.
+#[derive(Clone, Debug)]
+pub struct Elements<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Elements<'a> {
+    pub fn new(data: &'a [u8]) -> Elements<'a> {
+        Elements { data }
+    }
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Element> {
+        let id = *self.data.first()?;
+        let len = *self.data.get(1)? as usize;
+        let body = match self.data.get(2..2 + len) {
+            Some(body) => body,
+            None => {
+                self.data = &[];
+                return None;
+            }
+        };
+
+        self.data = &self.data[2 + len..];
+        Some(Element::parse(id, body))
+    }
+}
+
+/// Read a little endian u16 at `offset`
+pub(crate) fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a little endian u32 at `offset`
+pub(crate) fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// A rate of the Supported Rates or Extended Supported Rates elements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rate {
+    /// Rate in units of 500 kbit/s
+    pub value: u8,
+    /// The rate is part of the basic rate set, mandatory for every station
+    pub basic: bool,
+}
+
+impl Rate {
+    fn parse_all(data: &[u8]) -> Vec<Rate> {
+        data.iter()
+            .map(|byte| Rate {
+                value: byte & 0x7f,
+                basic: byte & 0x80 != 0,
+            })
+            .collect()
+    }
+
+    /// Rate in MBit/s
+    pub fn mbit_per_sec(&self) -> f64 {
+        self.value as f64 / 2.0
+    }
+}
+
+impl fmt::Display for Rate {
+    /// Same format as iw, e.g. "5.5*" for a basic rate of 5.5 MBit/s
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}{}",
+            self.value / 2,
+            5 * (self.value & 1),
+            if self.basic { "*" } else { "" }
+        )
+    }
+}
+
+/// Traffic Indication Map element
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tim {
+    /// Beacons before the next DTIM, 0 when this beacon is a DTIM
+    pub dtim_count: u8,
+    /// DTIM period, in beacon intervals
+    pub dtim_period: u8,
+    pub bitmap_control: u8,
+    pub partial_virtual_bitmap: Vec<u8>,
+}
+
+impl Tim {
+    fn parse(data: &[u8]) -> Option<Tim> {
+        if data.len() < 3 {
+            return None;
+        }
+
+        Some(Tim {
+            dtim_count: data[0],
+            dtim_period: data[1],
+            bitmap_control: data[2],
+            partial_virtual_bitmap: data[3..].to_vec(),
+        })
+    }
+
+    /// Group addressed frames are buffered by the AP
+    pub fn multicast_buffered(&self) -> bool {
+        self.bitmap_control & 1 != 0
+    }
+}
+
+/// Country element
+#[derive(Clone, Debug, PartialEq)]
+pub struct Country {
+    /// ISO 3166-1 alpha-2 country code
+    pub alpha2: String,
+    /// Environment: b' ' for any, b'I' for indoor, b'O' for outdoor
+    pub environment: u8,
+    pub triplets: Vec<CountryTriplet>,
+}
+
+/// Triplet of a Country element
+#[derive(Clone, Debug, PartialEq)]
+pub enum CountryTriplet {
+    /// Channels sharing the same transmit power limit
+    Subband {
+        first_channel: u8,
+        num_channels: u8,
+        max_tx_power: Dbm,
+    },
+    /// Operating class the following subbands apply to
+    Operating {
+        extension_id: u8,
+        operating_class: u8,
+        coverage_class: u8,
+    },
+}
+
+impl Country {
+    fn parse(data: &[u8]) -> Option<Country> {
+        if data.len() < 3 {
+            return None;
+        }
+
+        let triplets = data[3..]
+            .chunks_exact(3)
+            .map(|triplet| {
+                if triplet[0] >= 201 {
+                    CountryTriplet::Operating {
+                        extension_id: triplet[0],
+                        operating_class: triplet[1],
+                        coverage_class: triplet[2],
+                    }
+                } else {
+                    CountryTriplet::Subband {
+                        first_channel: triplet[0],
+                        num_channels: triplet[1],
+                        max_tx_power: Dbm(triplet[2] as i8 as i32),
+                    }
+                }
+            })
+            .collect();
+
+        Some(Country {
+            alpha2: String::from_utf8_lossy(&data[0..2]).to_string(),
+            environment: data[2],
+            triplets,
+        })
+    }
+}
+
+/// BSS Load element
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BssLoad {
+    /// Stations associated with the BSS
+    pub station_count: u16,
+    /// Time the medium was sensed busy, scaled to 0..255
+    pub channel_utilization: u8,
+    /// Remaining medium time for admission control, in units of 32 usec per second
+    pub available_admission_capacity: u16,
+}
+
+impl BssLoad {
+    fn parse(data: &[u8]) -> Option<BssLoad> {
+        Some(BssLoad {
+            station_count: le_u16(data, 0)?,
+            channel_utilization: *data.get(2)?,
+            available_admission_capacity: le_u16(data, 3)?,
+        })
+    }
+}
+
+/// Mobility Domain element, advertised by APs supporting fast BSS transition (802.11r)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MobilityDomain {
+    /// Mobility domain identifier
+    pub mdid: u16,
+    pub ft_capability: u8,
+}
+
+impl MobilityDomain {
+    fn parse(data: &[u8]) -> Option<MobilityDomain> {
+        Some(MobilityDomain {
+            mdid: le_u16(data, 0)?,
+            ft_capability: *data.get(2)?,
+        })
+    }
+
+    /// Fast BSS transition over the distribution system is supported
+    pub fn ft_over_ds(&self) -> bool {
+        self.ft_capability & 1 != 0
+    }
+
+    /// Resource request protocol is supported
+    pub fn resource_request(&self) -> bool {
+        self.ft_capability & 2 != 0
+    }
+}
+
+/// Extended Capabilities element, a bit field of variable length
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtendedCapabilities(pub Vec<u8>);
+
+impl ExtendedCapabilities {
+    pub const BSS_TRANSITION: usize = 19;
+    pub const INTERWORKING: usize = 31;
+    pub const OPERATING_MODE_NOTIFICATION: usize = 62;
+
+    /// Whether a capability bit is set, bits missing from the element are not
+    pub fn has(&self, bit: usize) -> bool {
+        self.0
+            .get(bit / 8)
+            .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+    }
+}
+
+/// Vendor Specific element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vendor {
+    /// Organizationally unique identifier of the vendor
+    pub oui: [u8; 3],
+    /// Vendor defined body, usually starting with a type
+    pub data: Vec<u8>,
+}
+
+impl Vendor {
+    /// OUI of Microsoft, used by WPA, WMM and WPS elements
+    pub const OUI_MICROSOFT: [u8; 3] = [0x00, 0x50, 0xf2];
+    /// OUI of the Wi-Fi Alliance
+    pub const OUI_WFA: [u8; 3] = [0x50, 0x6f, 0x9a];
+
+    fn parse(data: &[u8]) -> Option<Vendor> {
+        let oui = data.get(0..3)?;
+        Some(Vendor {
+            oui: [oui[0], oui[1], oui[2]],
+            data: data[3..].to_vec(),
+        })
+    }
+
+    /// Vendor defined type, the first byte after the OUI
+    pub fn vendor_type(&self) -> Option<u8> {
+        self.data.first().cloned()
+    }
+
+    /// Decode a legacy WPA element, a Microsoft vendor element of type 1
+    pub fn wpa(&self) -> Option<Rsn> {
+        if self.oui != Vendor::OUI_MICROSOFT || self.vendor_type() != Some(1) {
+            return None;
+        }
+        Rsn::parse_wpa(&self.data[1..])
+    }
+}
+
+#[cfg(test)]
+mod test_ie {
+    use super::*;
+
+    #[test]
+    fn test_elements() {
+        let ies = vec![
+            0, 8, 83, 70, 82, 45, 49, 99, 50, 56, 1, 8, 130, 132, 139, 150, 36, 48, 72, 108, 3, 1,
+            1, 5, 4, 0, 1, 0, 0, 7, 6, 68, 69, 32, 1, 13, 20, 42, 1, 0, 11, 5, 1, 0, 80, 0, 0, 54,
+            3, 52, 18, 1, 127, 8, 4, 0, 8, 0, 0, 0, 0, 64, 221, 9, 0, 16, 24, 2, 1, 0, 12, 0, 0,
+        ];
+        let elements: Vec<Element> = Elements::new(&ies).collect();
+
+        assert_eq!(
+            elements,
+            vec![
+                Element::Ssid(Ssid(b"SFR-1c28".to_vec())),
+                Element::SupportedRates(Rate::parse_all(&[130, 132, 139, 150, 36, 48, 72, 108])),
+                Element::DsParameter(1),
+                Element::Tim(Tim {
+                    dtim_count: 0,
+                    dtim_period: 1,
+                    bitmap_control: 0,
+                    partial_virtual_bitmap: vec![0],
+                }),
+                Element::Country(Country {
+                    alpha2: "DE".to_string(),
+                    environment: b' ',
+                    triplets: vec![CountryTriplet::Subband {
+                        first_channel: 1,
+                        num_channels: 13,
+                        max_tx_power: Dbm(20),
+                    }],
+                }),
+                Element::Unknown {
+                    id: 42,
+                    data: vec![0]
+                },
+                Element::BssLoad(BssLoad {
+                    station_count: 1,
+                    channel_utilization: 80,
+                    available_admission_capacity: 0,
+                }),
+                Element::MobilityDomain(MobilityDomain {
+                    mdid: 0x1234,
+                    ft_capability: 1,
+                }),
+                Element::ExtendedCapabilities(ExtendedCapabilities(vec![4, 0, 8, 0, 0, 0, 0, 64])),
+                Element::Vendor(Vendor {
+                    oui: [0, 16, 24],
+                    data: vec![2, 1, 0, 12, 0, 0],
+                }),
+            ]
+        );
+
+        let rates: Vec<String> = Rate::parse_all(&[130, 132, 139, 150, 36])
+            .iter()
+            .map(|rate| rate.to_string())
+            .collect();
+        assert_eq!(rates, vec!["1.0*", "2.0*", "5.5*", "11.0*", "18.0"]);
+    }
+
+    #[test]
+    fn test_truncated_elements() {
+        let ies = vec![
+            0, 4, 116, 101, 115, 116, 45, 26, 188, 9, 27, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 48, 20, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 1,
+            0, 0, 15, 172, 2, 0, 0, 255, 7, 36, 0, 0, 0, 0, 0, 0, 255, 3, 35, 1, 2, 221, 4, 0, 80,
+            242, 1, 221, 10, 1, 2,
+        ];
+
+        // Every prefix must decode without panicking, and stop before the truncated element
+        for len in 0..ies.len() {
+            let elements: Vec<Element> = Elements::new(&ies[..len]).collect();
+            assert!(elements.len() <= 6);
+        }
+
+        // Declared lengths too short for the element body
+        for id in 0..=255u8 {
+            for len in 0..8 {
+                let body = vec![0xff; len];
+                let mut element = vec![id, len as u8];
+                element.extend(&body);
+                Elements::new(&element).for_each(drop);
+            }
+        }
+
+        let elements: Vec<Element> = Elements::new(&ies).collect();
+        assert_eq!(elements.len(), 6);
+        assert_eq!(
+            elements[4],
+            Element::Unknown {
+                id: 255,
+                data: vec![35, 1, 2]
+            }
+        );
+    }
+}
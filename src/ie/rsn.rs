@@ -0,0 +1,496 @@
+use crate::attr::{Nl80211KeyType, Nl80211Mfp};
+use crate::ie::le_u16;
+use std::fmt;
+
+/// A cipher or AKM suite selector: an OUI followed by a suite type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Suite {
+    pub oui: [u8; 3],
+    pub suite_type: u8,
+}
+
+impl Suite {
+    /// OUI of the suites defined by 802.11
+    pub const OUI_IEEE80211: [u8; 3] = [0x00, 0x0f, 0xac];
+    /// OUI of the suites of the legacy WPA element
+    pub const OUI_WPA: [u8; 3] = [0x00, 0x50, 0xf2];
+
+    fn parse(data: &[u8]) -> Suite {
+        Suite {
+            oui: [data[0], data[1], data[2]],
+            suite_type: data[3],
+        }
+    }
+
+    /// Suite from a selector as used by nl80211, e.g. 0x000FAC04 for CCMP-128
+    pub fn from_selector(selector: u32) -> Suite {
+        Suite::parse(&selector.to_be_bytes())
+    }
+
+    /// Selector of the suite as used by nl80211
+    pub fn selector(&self) -> u32 {
+        u32::from_be_bytes([self.oui[0], self.oui[1], self.oui[2], self.suite_type])
+    }
+
+    /// Suite type when defined by 802.11, or by WPA which uses the same types for its ciphers
+    fn ieee80211_type(&self, wpa: bool) -> Option<u8> {
+        if self.oui == Suite::OUI_IEEE80211 || (wpa && self.oui == Suite::OUI_WPA) {
+            Some(self.suite_type)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Suite {
+    /// Same format as iw for unknown suites, e.g. "00-0F-AC:4"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02X}-{:02X}-{:02X}:{}",
+            self.oui[0], self.oui[1], self.oui[2], self.suite_type
+        )
+    }
+}
+
+/// A cipher suite
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cipher {
+    /// Pairwise keys are not used, the group cipher is used for unicast traffic too
+    UseGroup,
+    Wep40,
+    Tkip,
+    Ccmp128,
+    Wep104,
+    BipCmac128,
+    GroupNotAllowed,
+    Gcmp128,
+    Gcmp256,
+    Ccmp256,
+    BipGmac128,
+    BipGmac256,
+    BipCmac256,
+    Other(Suite),
+}
+
+impl Cipher {
+    /// Cipher from a selector as used by nl80211, e.g. 0x000FAC04 for CCMP-128
+    pub fn from_selector(selector: u32) -> Cipher {
+        Cipher::from_suite(Suite::from_selector(selector))
+    }
+
+    fn from_suite(suite: Suite) -> Cipher {
+        let suite_type = match suite.ieee80211_type(true) {
+            Some(suite_type) => suite_type,
+            None => return Cipher::Other(suite),
+        };
+
+        match suite_type {
+            0 => Cipher::UseGroup,
+            1 => Cipher::Wep40,
+            2 => Cipher::Tkip,
+            4 => Cipher::Ccmp128,
+            5 => Cipher::Wep104,
+            6 if suite.oui == Suite::OUI_IEEE80211 => Cipher::BipCmac128,
+            7 if suite.oui == Suite::OUI_IEEE80211 => Cipher::GroupNotAllowed,
+            8 if suite.oui == Suite::OUI_IEEE80211 => Cipher::Gcmp128,
+            9 if suite.oui == Suite::OUI_IEEE80211 => Cipher::Gcmp256,
+            10 if suite.oui == Suite::OUI_IEEE80211 => Cipher::Ccmp256,
+            11 if suite.oui == Suite::OUI_IEEE80211 => Cipher::BipGmac128,
+            12 if suite.oui == Suite::OUI_IEEE80211 => Cipher::BipGmac256,
+            13 if suite.oui == Suite::OUI_IEEE80211 => Cipher::BipCmac256,
+            _ => Cipher::Other(suite),
+        }
+    }
+}
+
+impl fmt::Display for Cipher {
+    /// Same names as iw, e.g. "CCMP" or "GCMP-256"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Cipher::UseGroup => "Use group cipher suite",
+            Cipher::Wep40 => "WEP-40",
+            Cipher::Tkip => "TKIP",
+            Cipher::Ccmp128 => "CCMP",
+            Cipher::Wep104 => "WEP-104",
+            Cipher::BipCmac128 => "AES-128-CMAC",
+            Cipher::GroupNotAllowed => "NO-GROUP",
+            Cipher::Gcmp128 => "GCMP-128",
+            Cipher::Gcmp256 => "GCMP-256",
+            Cipher::Ccmp256 => "CCMP-256",
+            Cipher::BipGmac128 => "BIP-GMAC-128",
+            Cipher::BipGmac256 => "BIP-GMAC-256",
+            Cipher::BipCmac256 => "BIP-CMAC-256",
+            Cipher::Other(suite) => return write!(f, "{}", suite),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An authentication and key management (AKM) suite
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Akm {
+    Ieee8021x,
+    Psk,
+    FtIeee8021x,
+    FtPsk,
+    Ieee8021xSha256,
+    PskSha256,
+    Tdls,
+    Sae,
+    FtSae,
+    ApPeerKey,
+    /// 802.1X with Suite B, 128 bits security
+    Ieee8021xSuiteB,
+    /// 802.1X with Suite B, 192 bits security (WPA3-Enterprise 192 bits)
+    Ieee8021xSuiteB192,
+    FtIeee8021xSha384,
+    FilsSha256,
+    FilsSha384,
+    FtFilsSha256,
+    FtFilsSha384,
+    Owe,
+    FtPskSha384,
+    PskSha384,
+    /// SAE with group dependent hash
+    SaeExt,
+    FtSaeExt,
+    Other(Suite),
+}
+
+impl Akm {
+    fn from_suite(suite: Suite, wpa: bool) -> Akm {
+        let suite_type = match suite.ieee80211_type(wpa) {
+            Some(suite_type) => suite_type,
+            None => return Akm::Other(suite),
+        };
+
+        match suite_type {
+            1 => Akm::Ieee8021x,
+            2 => Akm::Psk,
+            _ if wpa => Akm::Other(suite),
+            3 => Akm::FtIeee8021x,
+            4 => Akm::FtPsk,
+            5 => Akm::Ieee8021xSha256,
+            6 => Akm::PskSha256,
+            7 => Akm::Tdls,
+            8 => Akm::Sae,
+            9 => Akm::FtSae,
+            10 => Akm::ApPeerKey,
+            11 => Akm::Ieee8021xSuiteB,
+            12 => Akm::Ieee8021xSuiteB192,
+            13 => Akm::FtIeee8021xSha384,
+            14 => Akm::FilsSha256,
+            15 => Akm::FilsSha384,
+            16 => Akm::FtFilsSha256,
+            17 => Akm::FtFilsSha384,
+            18 => Akm::Owe,
+            19 => Akm::FtPskSha384,
+            20 => Akm::PskSha384,
+            24 => Akm::SaeExt,
+            25 => Akm::FtSaeExt,
+            _ => Akm::Other(suite),
+        }
+    }
+
+    /// Authentication with a pre-shared key, without SAE
+    pub fn is_psk(&self) -> bool {
+        matches!(
+            self,
+            Akm::Psk | Akm::FtPsk | Akm::PskSha256 | Akm::FtPskSha384 | Akm::PskSha384
+        )
+    }
+
+    /// Authentication with SAE (WPA3-Personal)
+    pub fn is_sae(&self) -> bool {
+        matches!(self, Akm::Sae | Akm::FtSae | Akm::SaeExt | Akm::FtSaeExt)
+    }
+
+    /// Authentication with 802.1X
+    pub fn is_ieee8021x(&self) -> bool {
+        matches!(
+            self,
+            Akm::Ieee8021x
+                | Akm::FtIeee8021x
+                | Akm::Ieee8021xSha256
+                | Akm::Ieee8021xSuiteB
+                | Akm::Ieee8021xSuiteB192
+                | Akm::FtIeee8021xSha384
+        )
+    }
+}
+
+impl fmt::Display for Akm {
+    /// Same names as iw, e.g. "PSK" or "SAE"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Akm::Ieee8021x => "IEEE 802.1X",
+            Akm::Psk => "PSK",
+            Akm::FtIeee8021x => "FT/IEEE 802.1X",
+            Akm::FtPsk => "FT/PSK",
+            Akm::Ieee8021xSha256 => "IEEE 802.1X/SHA-256",
+            Akm::PskSha256 => "PSK/SHA-256",
+            Akm::Tdls => "TDLS/TPK",
+            Akm::Sae => "SAE",
+            Akm::FtSae => "FT/SAE",
+            Akm::ApPeerKey => "APPeerKey",
+            Akm::Ieee8021xSuiteB => "IEEE 802.1X/SUITE-B",
+            Akm::Ieee8021xSuiteB192 => "IEEE 802.1X/SUITE-B-192",
+            Akm::FtIeee8021xSha384 => "FT/IEEE 802.1X/SHA-384",
+            Akm::FilsSha256 => "FILS/SHA-256",
+            Akm::FilsSha384 => "FILS/SHA-384",
+            Akm::FtFilsSha256 => "FT/FILS/SHA-256",
+            Akm::FtFilsSha384 => "FT/FILS/SHA-384",
+            Akm::Owe => "OWE",
+            Akm::FtPskSha384 => "FT/PSK/SHA-384",
+            Akm::PskSha384 => "PSK/SHA-384",
+            Akm::SaeExt => "SAE-EXT-KEY",
+            Akm::FtSaeExt => "FT/SAE-EXT-KEY",
+            Akm::Other(suite) => return write!(f, "{}", suite),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// RSN element, or legacy WPA element which starts with the same fields
+///
+/// Every field after the version is optional, the element may stop after any of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rsn {
+    pub version: u16,
+    pub group_cipher: Option<Cipher>,
+    pub pairwise_ciphers: Vec<Cipher>,
+    pub akm_suites: Vec<Akm>,
+    /// RSN capabilities bit field
+    pub capabilities: Option<u16>,
+    pub pmkids: Vec<[u8; 16]>,
+    pub group_mgmt_cipher: Option<Cipher>,
+}
+
+/// Read the fields of an RSN element, keeping track of the remaining bytes
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(..len)?;
+        self.data = &self.data[len..];
+        Some(bytes)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).and_then(|bytes| le_u16(bytes, 0))
+    }
+
+    fn suite(&mut self) -> Option<Suite> {
+        self.take(4).map(Suite::parse)
+    }
+
+    /// A count followed by as many suites
+    fn suite_list(&mut self) -> Option<Vec<Suite>> {
+        let count = self.u16()? as usize;
+        let suites = self.take(count.checked_mul(4)?)?;
+        Some(suites.chunks_exact(4).map(Suite::parse).collect())
+    }
+}
+
+impl Rsn {
+    /// Decode the body of an RSN element, `None` when a field is truncated
+    pub(crate) fn parse(data: &[u8]) -> Option<Rsn> {
+        Rsn::parse_suites(data, false)
+    }
+
+    /// Decode a legacy WPA element, the body of a vendor element following the OUI and type
+    pub(crate) fn parse_wpa(data: &[u8]) -> Option<Rsn> {
+        Rsn::parse_suites(data, true)
+    }
+
+    fn parse_suites(data: &[u8], wpa: bool) -> Option<Rsn> {
+        let mut reader = Reader { data };
+        let mut rsn = Rsn {
+            version: reader.u16()?,
+            ..Rsn::default()
+        };
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.group_cipher = Some(Cipher::from_suite(reader.suite()?));
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.pairwise_ciphers = reader.suite_list()?.into_iter().map(Cipher::from_suite).collect();
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.akm_suites = reader
+            .suite_list()?
+            .into_iter()
+            .map(|suite| Akm::from_suite(suite, wpa))
+            .collect();
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.capabilities = Some(reader.u16()?);
+
+        if reader.is_empty() || wpa {
+            return Some(rsn);
+        }
+        let count = reader.u16()? as usize;
+        let pmkids = reader.take(count.checked_mul(16)?)?;
+        rsn.pmkids = pmkids
+            .chunks_exact(16)
+            .map(|chunk| {
+                let mut pmkid = [0; 16];
+                pmkid.copy_from_slice(chunk);
+                pmkid
+            })
+            .collect();
+
+        if reader.is_empty() {
+            return Some(rsn);
+        }
+        rsn.group_mgmt_cipher = Some(Cipher::from_suite(reader.suite()?));
+
+        Some(rsn)
+    }
+
+    fn capability(&self, bit: u16) -> bool {
+        self.capabilities.is_some_and(|capabilities| capabilities & bit != 0)
+    }
+
+    /// The AP supports pre-authentication
+    pub fn preauth(&self) -> bool {
+        self.capability(1 << 0)
+    }
+
+    /// Protection of management frames (802.11w) is supported
+    pub fn mfp_capable(&self) -> bool {
+        self.capability(1 << 7)
+    }
+
+    /// Protection of management frames (802.11w) is mandatory
+    pub fn mfp_required(&self) -> bool {
+        self.capability(1 << 6)
+    }
+
+    /// Use of management frame protection
+    pub fn mfp(&self) -> Nl80211Mfp {
+        if self.mfp_required() {
+            Nl80211Mfp::MfpRequired
+        } else if self.mfp_capable() {
+            Nl80211Mfp::MfpOptional
+        } else {
+            Nl80211Mfp::MfpNo
+        }
+    }
+
+    /// Number of PTKSA replay counters: 1, 2, 4 or 16
+    pub fn ptksa_replay_counters(&self) -> u8 {
+        replay_counters(self.capabilities.unwrap_or(0) >> 2)
+    }
+
+    /// Number of GTKSA replay counters: 1, 2, 4 or 16
+    pub fn gtksa_replay_counters(&self) -> u8 {
+        replay_counters(self.capabilities.unwrap_or(0) >> 4)
+    }
+
+    /// Ciphers used for a type of key, CCMP-128 when the field is missing
+    pub fn ciphers(&self, key_type: Nl80211KeyType) -> Vec<Cipher> {
+        match key_type {
+            Nl80211KeyType::KeytypeGroup => vec![self.group_cipher.unwrap_or(Cipher::Ccmp128)],
+            Nl80211KeyType::KeytypePairwise if self.pairwise_ciphers.is_empty() => {
+                vec![Cipher::Ccmp128]
+            }
+            Nl80211KeyType::KeytypePairwise => self.pairwise_ciphers.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// AKM suites, 802.1X when the field is missing
+    pub fn akms(&self) -> Vec<Akm> {
+        if self.akm_suites.is_empty() {
+            vec![Akm::Ieee8021x]
+        } else {
+            self.akm_suites.clone()
+        }
+    }
+}
+
+fn replay_counters(field: u16) -> u8 {
+    match field & 0x3 {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        _ => 16,
+    }
+}
+
+#[cfg(test)]
+mod test_rsn {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let data = vec![
+            1, 0, 0, 15, 172, 4, 2, 0, 0, 15, 172, 4, 0, 15, 172, 2, 1, 0, 0, 15, 172, 2, 12, 0,
+        ];
+        let rsn = Rsn::parse(&data).unwrap();
+
+        assert_eq!(
+            rsn,
+            Rsn {
+                version: 1,
+                group_cipher: Some(Cipher::Ccmp128),
+                pairwise_ciphers: vec![Cipher::Ccmp128, Cipher::Tkip],
+                akm_suites: vec![Akm::Psk],
+                capabilities: Some(12),
+                ..Rsn::default()
+            }
+        );
+        assert_eq!(rsn.mfp(), Nl80211Mfp::MfpNo);
+        assert_eq!(rsn.ptksa_replay_counters(), 16);
+        assert_eq!(rsn.gtksa_replay_counters(), 1);
+
+        // Legacy WPA element, following the Microsoft OUI and the type
+        let wpa = vec![1, 0, 0, 80, 242, 2, 1, 0, 0, 80, 242, 2, 1, 0, 0, 80, 242, 2];
+        let wpa = Rsn::parse_wpa(&wpa).unwrap();
+        assert_eq!(wpa.ciphers(Nl80211KeyType::KeytypePairwise), vec![Cipher::Tkip]);
+        assert_eq!(wpa.akms(), vec![Akm::Psk]);
+
+        let unknown = Suite::from_selector(0x00_10_18_01);
+        assert_eq!(unknown.selector(), 0x00_10_18_01);
+        assert_eq!(Cipher::from_suite(unknown).to_string(), "00-10-18:1");
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let data = vec![
+            1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 8, 192, 0, 0, 0, 0, 15,
+            172, 6,
+        ];
+        let rsn = Rsn::parse(&data).unwrap();
+        assert_eq!(rsn.akm_suites, vec![Akm::Sae]);
+        assert_eq!(rsn.mfp(), Nl80211Mfp::MfpRequired);
+        assert_eq!(rsn.group_mgmt_cipher, Some(Cipher::BipCmac128));
+
+        // Only the version, every other field takes its default value
+        let rsn = Rsn::parse(&data[..2]).unwrap();
+        assert_eq!(rsn.ciphers(Nl80211KeyType::KeytypeGroup), vec![Cipher::Ccmp128]);
+        assert_eq!(rsn.akms(), vec![Akm::Ieee8021x]);
+        // Suite count larger than the element
+        assert_eq!(Rsn::parse(&[1, 0, 0, 15, 172, 4, 255, 255, 0, 15, 172, 4]), None);
+        // Truncated in the middle of a field
+        for len in &[1, 3, 7, 13, 19, 25] {
+            assert_eq!(Rsn::parse(&data[..*len]), None);
+        }
+    }
+}
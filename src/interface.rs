@@ -5,84 +5,77 @@ use crate::parse_attr::parse_u32;
 use crate::parse_attr::parse_u64;
 use crate::socket::Socket;
 use crate::station::Station;
+use crate::error::Nl80211Error;
+use crate::types::{Dbm, MacAddr, Mbm, Mhz, Ssid};
 use neli::nlattr::AttrHandle;
 
-use crate::parse_attr::{parse_hex, parse_string};
+use crate::parse_attr::{fixed_payload, parse_i32, parse_string};
+use std::convert::{TryFrom, TryInto};
 
 /// A struct representing a wifi interface
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Interface {
     /// A netlink interface index. This index is used to fetch extra information with nl80211
-    pub index: Option<Vec<u8>>,
-    /// Interface essid
-    pub ssid: Option<Vec<u8>>,
+    pub ifindex: u32,
+    /// Interface name
+    pub name: String,
     /// Interface MAC address
-    pub mac: Option<Vec<u8>>,
-    /// Interface name (u8, String)
-    pub name: Option<Vec<u8>>,
-    /// Interface frequency of the selected channel (u32, MHz)
-    pub frequency: Option<Vec<u8>>,
-    /// Interface chanel
-    pub channel: Option<Vec<u8>>,
-    /// Interface transmit power level in signed mBm units.
-    pub power: Option<Vec<u8>>,
+    pub mac: MacAddr,
+    /// Interface essid, when connected
+    pub ssid: Option<Ssid>,
+    /// Interface frequency of the selected channel
+    pub frequency: Option<Mhz>,
+    /// Interface channel width (nl80211_chan_width)
+    pub channel_width: Option<u32>,
+    /// Interface transmit power level
+    pub tx_power: Option<Mbm>,
     /// index of wiphy to operate on, cf. /sys/class/ieee80211/<phyname>/index
-    pub phy: Option<Vec<u8>>,
-    /// Wireless device identifier, used for pseudo-devices that don't have a netdev (u64)
-    pub device: Option<Vec<u8>>,
+    pub wiphy: u32,
+    /// Wireless device identifier, used for pseudo-devices that don't have a netdev
+    pub wdev: u64,
+    /// Interface type
+    pub iftype: Nl80211Iftype,
+    /// Attributes as sent by the kernel, kept for debugging
+    pub raw: Vec<(Nl80211Attr, Vec<u8>)>,
 }
 
 impl Interface {
-    pub fn default() -> Interface {
-        Interface {
-            index: None,
-            ssid: None,
-            mac: None,
-            name: None,
-            frequency: None,
-            channel: None,
-            power: None,
-            phy: None,
-            device: None,
-        }
-    }
-
     /// Get station info for this interface
-    pub fn get_station_info(&self) -> Result<Station, neli::err::NlError> {
-        if let Some(index) = &self.index {
-            Socket::connect()?.get_station_info(index)
-        } else {
-            Err(neli::err::NlError::new("Invalid interface index {:?}"))
-        }
+    pub fn get_station_info(&self) -> Result<Station, Nl80211Error> {
+        Socket::connect()?.get_station_info(self.ifindex)
     }
 }
 
 impl ParseNlAttr for Interface {
     /// Parse netlink messages returned by the nl80211 command CmdGetInterface
-    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Interface {
+    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Result<Interface, Nl80211Error> {
         for attr in handle.iter() {
             match attr.nla_type {
-                Nl80211Attr::AttrIfindex => {
-                    self.index = Some(attr.payload.clone());
-                }
-                Nl80211Attr::AttrSsid => {
-                    self.ssid = Some(attr.payload.clone());
-                }
+                Nl80211Attr::AttrIfindex => self.ifindex = parse_u32(attr)?,
+                Nl80211Attr::AttrSsid => self.ssid = Some(Ssid(attr.payload.clone())),
                 Nl80211Attr::AttrMac => {
-                    self.mac = Some(attr.payload.clone());
+                    self.mac = MacAddr(fixed_payload(attr, 6)?.try_into().unwrap());
                 }
                 Nl80211Attr::AttrIfname => {
-                    self.name = Some(attr.payload.clone());
+                    self.name = parse_string(&attr.payload).trim_end_matches('\0').to_string();
+                }
+                Nl80211Attr::AttrWiphyFreq => self.frequency = Some(Mhz(parse_u32(attr)?)),
+                Nl80211Attr::AttrChannelWidth => self.channel_width = Some(parse_u32(attr)?),
+                Nl80211Attr::AttrWiphyTxPowerLevel => {
+                    self.tx_power = Some(Mbm(parse_i32(attr)?))
+                }
+                Nl80211Attr::AttrWiphy => self.wiphy = parse_u32(attr)?,
+                Nl80211Attr::AttrWdev => self.wdev = parse_u64(attr)?,
+                Nl80211Attr::AttrIftype => {
+                    // Values too large for the enumeration are unknown, not truncated
+                    let iftype = u16::try_from(parse_u32(attr)?).unwrap_or(u16::MAX);
+                    self.iftype = Nl80211Iftype::from(iftype)
                 }
-                Nl80211Attr::AttrWiphyFreq => self.frequency = Some(attr.payload.clone()),
-                Nl80211Attr::AttrChannelWidth => self.channel = Some(attr.payload.clone()),
-                Nl80211Attr::AttrWiphyTxPowerLevel => self.power = Some(attr.payload.clone()),
-                Nl80211Attr::AttrWiphy => self.phy = Some(attr.payload.clone()),
-                Nl80211Attr::AttrWdev => self.device = Some(attr.payload.clone()),
                 _ => (),
             }
+            self.raw.push((attr.nla_type.clone(), attr.payload.clone()));
         }
-        self.to_owned()
+        Ok(self.to_owned())
     }
 }
 
@@ -91,39 +84,26 @@ impl fmt::Display for Interface {
         let mut result = Vec::new();
 
         if let Some(ssid) = &self.ssid {
-            result.push(format!("essid : {}", parse_string(&ssid)))
+            result.push(format!("essid : {}", ssid))
         };
 
-        if let Some(mac) = &self.mac {
-            result.push(format!("mac : {}", parse_hex(&mac)))
-        };
-
-        if let Some(name) = &self.name {
-            result.push(format!("interface : {}", parse_string(&name)))
-        };
+        result.push(format!("mac : {}", self.mac));
+        result.push(format!("interface : {}", self.name));
 
         if let Some(frequency) = &self.frequency {
-            result.push(format!(
-                "frequency : {} Ghz",
-                parse_u32(frequency) as f64 / 1000.00
-            ))
-        };
-
-        if let Some(chanel) = &self.channel {
-            result.push(format!("channel : {}", parse_u32(chanel)))
+            result.push(format!("frequency : {} Ghz", frequency.ghz()))
         };
 
-        if let Some(power) = &self.power {
-            result.push(format!("power : {} dBm", parse_u32(power) / 100))
+        if let Some(channel_width) = &self.channel_width {
+            result.push(format!("channel width : {}", channel_width))
         };
 
-        if let Some(phy) = &self.phy {
-            result.push(format!("phy : {}", parse_u32(phy)))
+        if let Some(tx_power) = &self.tx_power {
+            result.push(format!("power : {}", Dbm::from(*tx_power)))
         };
 
-        if let Some(device) = &self.device {
-            result.push(format!("device : {}", parse_u64(device)))
-        };
+        result.push(format!("phy : {}", self.wiphy));
+        result.push(format!("device : {}", self.wdev));
 
         write!(f, "{}", result.join("\n"))
     }
@@ -138,22 +118,24 @@ mod test_interface {
     #[test]
     fn test_pretty_format() {
         let interface = Interface {
-            index: Some(vec![3, 0, 0, 0]),
-            ssid: Some(vec![101, 100, 117, 114, 111, 97, 109]),
-            mac: Some(vec![255, 255, 255, 255, 255, 255]),
-            name: Some(vec![119, 108, 112, 53, 115, 48]),
-            frequency: Some(vec![108, 9, 0, 0]),
-            channel: Some(vec![1, 0, 0, 0]),
-            power: Some(vec![164, 6, 0, 0]),
-            phy: Some(vec![0, 0, 0, 0]),
-            device: Some(vec![1, 0, 0, 0, 0, 0, 0, 0]),
+            ifindex: 3,
+            ssid: Some(Ssid(b"eduroam".to_vec())),
+            mac: MacAddr([255, 255, 255, 255, 255, 255]),
+            name: "wlp5s0".to_string(),
+            frequency: Some(Mhz(2412)),
+            channel_width: Some(1),
+            tx_power: Some(Mbm(1700)),
+            wiphy: 0,
+            wdev: 1,
+            iftype: Nl80211Iftype::IftypeStation,
+            raw: vec![],
         };
 
         let expected_output = r#"essid : eduroam
         mac : FF:FF:FF:FF:FF:FF
         interface : wlp5s0
         frequency : 2.412 Ghz
-        channel : 1
+        channel width : 1
         power : 17 dBm
         phy : 0
         device : 1"#;
@@ -239,20 +221,38 @@ mod test_interface {
             },
         ];
 
-        let interface = Interface::default().parse(neli::nlattr::AttrHandle::Owned(handler));
+        let interface = Interface::default()
+            .parse(neli::nlattr::AttrHandle::Owned(handler))
+            .unwrap();
 
         let expected_interface = Interface {
-            index: Some(vec![3, 0, 0, 0]),
-            ssid: Some(vec![101, 100, 117, 114, 111, 97, 109]),
-            mac: Some(vec![255, 255, 255, 255, 255, 255]),
-            name: Some(vec![119, 108, 112, 53, 115, 48]),
-            frequency: Some(vec![108, 9, 0, 0]),
-            channel: Some(vec![1, 0, 0, 0]),
-            power: Some(vec![164, 6, 0, 0]),
-            phy: Some(vec![0, 0, 0, 0]),
-            device: Some(vec![1, 0, 0, 0, 0, 0, 0, 0]),
+            ifindex: 3,
+            ssid: Some(Ssid(b"eduroam".to_vec())),
+            mac: MacAddr([255, 255, 255, 255, 255, 255]),
+            name: "wlp5s0".to_string(),
+            frequency: Some(Mhz(2412)),
+            channel_width: Some(1),
+            tx_power: Some(Mbm(1700)),
+            wiphy: 0,
+            wdev: 1,
+            iftype: Nl80211Iftype::IftypeStation,
+            raw: interface.raw.clone(),
         };
 
-        assert_eq!(interface, expected_interface)
+        assert_eq!(interface, expected_interface);
+        assert_eq!(interface.raw.len(), 14);
+        assert_eq!(interface.raw[0], (AttrIfindex, vec![3, 0, 0, 0]));
+
+        let handler = vec![Nlattr::new(None, AttrMac, vec![255, 255, 255, 255]).unwrap()];
+        match Interface::default().parse(neli::nlattr::AttrHandle::Owned(handler)) {
+            Err(Nl80211Error::InvalidAttribute(err)) => assert_eq!(err.actual, 4),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let handler = vec![Nlattr::new(None, AttrIftype, vec![2, 0, 1, 0]).unwrap()];
+        let interface = Interface::default()
+            .parse(neli::nlattr::AttrHandle::Owned(handler))
+            .unwrap();
+        assert_eq!(interface.iftype, Nl80211Iftype::UnrecognizedVariant(u16::MAX));
     }
 }
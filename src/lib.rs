@@ -27,22 +27,24 @@
 //!
 //! use nl80211::Socket;
 //!
-//! fn main() -> Result<(), neli::err::NlError> {
+//! fn main() -> Result<(), nl80211::Nl80211Error> {
 //!   let interfaces = Socket::connect()?.get_interfaces_info()?;
 //!
 //!   for interface in interfaces {
 //!       println!("{:#?}", interface);
 //!
 //!       // Interface {
-//!       //   index: Some([3, 0, 0, 0]),
-//!       //   ssid: Some([101, 100, 117, 114, 111, 97, 109]),
-//!       //   mac: Some([255, 255, 255, 255, 255, 255]),
-//!       //   name: Some([119, 108, 112, 53, 115, 48]),
-//!       //   frequency: Some([108, 9, 0, 0]),
-//!       //   channel: Some([1, 0, 0, 0]),
-//!       //   power: Some([164, 6, 0, 0]),
-//!       //   phy: Some([0, 0, 0, 0]),
-//!       //   device: Some([1, 0, 0, 0, 0, 0, 0, 0])
+//!       //   ifindex: 3,
+//!       //   name: "wlp5s0",
+//!       //   mac: MacAddr([255, 255, 255, 255, 255, 255]),
+//!       //   ssid: Some(Ssid([101, 100, 117, 114, 111, 97, 109])),
+//!       //   frequency: Some(Mhz(2412)),
+//!       //   channel_width: Some(1),
+//!       //   tx_power: Some(Mbm(1700)),
+//!       //   wiphy: 0,
+//!       //   wdev: 1,
+//!       //   iftype: IftypeStation,
+//!       //   raw: [...]
 //!       // }
 //!
 //!       println!("{}", interface);
@@ -51,7 +53,7 @@
 //!       // mac : FF:FF:FF:FF:FF:FF
 //!       // interface : wlp5s0
 //!       // frequency : 2.412 Ghz
-//!       // channel : 1
+//!       // channel width : 1
 //!       // power : 17 dBm
 //!       // phy : 0
 //!       // device : 1
@@ -69,21 +71,24 @@
 //!
 //! use nl80211::Socket;
 //!
-//! fn main() -> Result<(), neli::err::NlError> {
+//! fn main() -> Result<(), nl80211::Nl80211Error> {
 //!   let interfaces = Socket::connect()?.get_interfaces_info()?;
 //!   for interface in interfaces {
 //!       let station = interface.get_station_info();
 //!       println!("{}", station?);
 //!
-//!       // bssid : FF:FF:FF:FF:FF:FF
+//!       // mac : FF:FF:FF:FF:FF:FF
 //!       // connected time : 35.816666 minutes
+//!       // inactive time : 40 ms
 //!       // beacon loss : 0
 //!       // signal : -60 dBm
 //!       // average signal : -61 dBm
+//!       // rx bytes : 210358541
+//!       // tx bytes : 8145874
 //!       // rx packets : 148983
 //!       // tx packets : 46335
-//!       // rx bitrate : 60 Mb/s
-//!       // tx bitrate : 140 Mb/s
+//!       // rx bitrate : 60.0 MBit/s
+//!       // tx bitrate : 144.4 MBit/s
 //!       // tx retries : 12578
 //!       // tx failed : 2
 //!   }
@@ -101,12 +106,33 @@ pub use attr::*;
 mod parse_attr;
 pub use parse_attr::*;
 mod socket;
-pub use socket::Socket;
+pub use socket::*;
+mod error;
+pub use error::*;
+mod pcap;
+pub use pcap::*;
+mod event;
+pub use event::*;
 mod consts;
 pub use consts::*;
 mod interface;
 pub use interface::*;
 mod station;
 pub use station::*;
+mod rate_info;
+pub use rate_info::*;
+mod tid_stats;
+pub use tid_stats::*;
 mod bss;
 pub use bss::*;
+mod scan;
+pub use scan::*;
+mod wiphy;
+pub use wiphy::*;
+mod channel;
+pub use channel::*;
+pub mod ie;
+mod security;
+pub use security::*;
+mod types;
+pub use types::*;
@@ -1,12 +1,23 @@
 use crate::attr::Nl80211Attr;
+use crate::error::Nl80211Error;
 use neli::nlattr::AttrHandle;
 
 /// Parse netlink messages attributes returned by a nl80211 command
-pub trait ParseNlAttr {
-    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Self;
+///
+/// Fails when an attribute can't be decoded, e.g. a payload shorter than the type it holds or a
+/// broken nested attribute.
+pub trait ParseNlAttr: Sized {
+    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Result<Self, Nl80211Error>;
 }
 
 /// Decode netlink payloads (Vec\<u8\>) to appropriate types
 pub trait NlPayloadDecode {
     fn decode(&mut self) -> Self;
 }
+
+/// Replies carrying nothing of interest, such as the ACK of a set command
+impl ParseNlAttr for () {
+    fn parse(&mut self, _handle: AttrHandle<Nl80211Attr>) -> Result<(), Nl80211Error> {
+        Ok(())
+    }
+}
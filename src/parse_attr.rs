@@ -1,7 +1,10 @@
+use crate::error::AttrError;
+use neli::nlattr::Nlattr;
 use std::convert::TryInto;
+use std::fmt;
 
 /// Parse a vec of bytes as hex String
-pub fn parse_hex(input: &Vec<u8>) -> String {
+pub fn parse_hex(input: &[u8]) -> String {
     let value: Vec<char> = hex::encode_upper(input).chars().collect();
     let split = value
         .chunks(2)
@@ -12,61 +15,77 @@ pub fn parse_hex(input: &Vec<u8>) -> String {
 }
 
 /// Parse a vec of bytes as a String
-pub fn parse_string(input: &Vec<u8>) -> String {
-    String::from_utf8_lossy(&input).to_owned().to_string()
+pub fn parse_string(input: &[u8]) -> String {
+    String::from_utf8_lossy(input).to_string()
 }
 
-/// Parse a vec of bytes as u8
-pub fn parse_u8(input: &Vec<u8>) -> u8 {
-    let to_array =
-        |slice: &[u8]| -> [u8; 1] { slice.try_into().expect("slice with incorrect length") };
-
-    u8::from_le_bytes(to_array(input))
+/// Payload of an attribute, checked against the length of the type it holds
+pub(crate) fn fixed_payload<T: fmt::Debug>(
+    attr: &Nlattr<T, Vec<u8>>,
+    expected: usize,
+) -> Result<&[u8], AttrError> {
+    if attr.payload.len() != expected {
+        return Err(AttrError {
+            attr: format!("{:?}", attr.nla_type),
+            expected,
+            actual: attr.payload.len(),
+        });
+    }
+    Ok(&attr.payload)
 }
 
-/// Parse a vec of bytes as i8
-pub fn parse_i8(input: &Vec<u8>) -> i8 {
-    let to_array =
-        |slice: &[u8]| -> [u8; 1] { slice.try_into().expect("slice with incorrect length") };
-
-    i8::from_le_bytes(to_array(input))
+/// Parse the payload of an attribute as u8
+pub fn parse_u8<T: fmt::Debug>(attr: &Nlattr<T, Vec<u8>>) -> Result<u8, AttrError> {
+    let bytes = fixed_payload(attr, 1)?;
+    Ok(u8::from_le_bytes(bytes.try_into().unwrap()))
 }
 
-/// Parse a vec of bytes as u16
-pub fn parse_u16(input: &Vec<u8>) -> u16 {
-    let to_array =
-        |slice: &[u8]| -> [u8; 2] { slice.try_into().expect("slice with incorrect length") };
-
-    u16::from_le_bytes(to_array(input))
+/// Parse the payload of an attribute as i8
+pub fn parse_i8<T: fmt::Debug>(attr: &Nlattr<T, Vec<u8>>) -> Result<i8, AttrError> {
+    let bytes = fixed_payload(attr, 1)?;
+    Ok(i8::from_le_bytes(bytes.try_into().unwrap()))
 }
 
-/// Parse a vec of bytes as u32
-pub fn parse_u32(input: &Vec<u8>) -> u32 {
-    let to_array =
-        |slice: &[u8]| -> [u8; 4] { slice.try_into().expect("slice with incorrect length") };
-
-    u32::from_le_bytes(to_array(input))
+/// Parse the payload of an attribute as u16
+pub fn parse_u16<T: fmt::Debug>(attr: &Nlattr<T, Vec<u8>>) -> Result<u16, AttrError> {
+    let bytes = fixed_payload(attr, 2)?;
+    Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
 }
 
-/// Parse a vec of bytes as i32
-pub fn parse_i32(input: &Vec<u8>) -> i32 {
-    let to_array =
-        |slice: &[u8]| -> [u8; 4] { slice.try_into().expect("slice with incorrect length") };
-
-    i32::from_le_bytes(to_array(input))
+/// Parse the payload of an attribute as u32
+pub fn parse_u32<T: fmt::Debug>(attr: &Nlattr<T, Vec<u8>>) -> Result<u32, AttrError> {
+    let bytes = fixed_payload(attr, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
 }
 
-/// Parse a vec of bytes as u64
-pub fn parse_u64(input: &Vec<u8>) -> u64 {
-    let to_array =
-        |slice: &[u8]| -> [u8; 8] { slice.try_into().expect("slice with incorrect length") };
+/// Parse the payload of an attribute as i32
+pub fn parse_i32<T: fmt::Debug>(attr: &Nlattr<T, Vec<u8>>) -> Result<i32, AttrError> {
+    let bytes = fixed_payload(attr, 4)?;
+    Ok(i32::from_le_bytes(bytes.try_into().unwrap()))
+}
 
-    u64::from_le_bytes(to_array(input))
+/// Parse the payload of an attribute as u64
+pub fn parse_u64<T: fmt::Debug>(attr: &Nlattr<T, Vec<u8>>) -> Result<u64, AttrError> {
+    let bytes = fixed_payload(attr, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
 }
 
 #[cfg(test)]
 mod test_type_conversion {
     use super::*;
+    use crate::attr::Nl80211Attr;
+
+    fn attr(payload: Vec<u8>) -> Nlattr<Nl80211Attr, Vec<u8>> {
+        Nlattr::new(None, Nl80211Attr::AttrIfindex, payload).unwrap()
+    }
+
+    fn error(expected: usize, actual: usize) -> AttrError {
+        AttrError {
+            attr: "AttrIfindex".to_string(),
+            expected,
+            actual,
+        }
+    }
 
     #[test]
     fn test_parse_hex() {
@@ -83,71 +102,73 @@ mod test_type_conversion {
 
     #[test]
     fn test_parse_u8() {
-        assert_eq!(parse_u8(&vec![8]), 8 as u8);
+        assert_eq!(parse_u8(&attr(vec![8])), Ok(8u8));
     }
 
     #[test]
-    #[should_panic]
-    fn test_parse_u8_should_panic() {
-        assert_eq!(parse_u8(&vec![8, 0]), 8 as u8);
+    fn test_parse_u8_wrong_length() {
+        assert_eq!(parse_u8(&attr(vec![8, 0])), Err(error(1, 2)));
     }
 
     #[test]
     fn test_parse_i8() {
-        assert_eq!(parse_i8(&vec![8]), 8 as i8);
+        assert_eq!(parse_i8(&attr(vec![0xf8])), Ok(-8i8));
     }
 
     #[test]
-    #[should_panic]
-    fn test_parse_i8_should_panic() {
-        assert_eq!(parse_i8(&vec![8, 0]), 8 as i8);
+    fn test_parse_i8_wrong_length() {
+        assert_eq!(parse_i8(&attr(vec![])), Err(error(1, 0)));
     }
 
     #[test]
     fn test_parse_u16() {
-        assert_eq!(parse_u16(&vec![1, 0]), 1 as u16);
+        assert_eq!(parse_u16(&attr(vec![1, 0])), Ok(1u16));
     }
 
     #[test]
-    #[should_panic]
-    fn test_parse_u16_should_panic() {
-        assert_eq!(parse_u16(&vec![1, 0, 0]), 1 as u16);
-        assert_eq!(parse_u16(&vec![1]), 1 as u16);
+    fn test_parse_u16_wrong_length() {
+        assert_eq!(parse_u16(&attr(vec![1, 0, 0])), Err(error(2, 3)));
+        assert_eq!(parse_u16(&attr(vec![1])), Err(error(2, 1)));
     }
 
     #[test]
     fn test_parse_u32() {
-        assert_eq!(parse_u32(&vec![1, 0, 0, 0]), 1 as u32);
+        assert_eq!(parse_u32(&attr(vec![1, 0, 0, 0])), Ok(1u32));
     }
 
     #[test]
-    #[should_panic]
-    fn test_parse_u32_should_panic() {
-        assert_eq!(parse_u32(&vec![1, 0, 0, 0, 0]), 1 as u32);
-        assert_eq!(parse_u32(&vec![1, 0, 0]), 1 as u32);
+    fn test_parse_u32_wrong_length() {
+        assert_eq!(parse_u32(&attr(vec![1, 0, 0, 0, 0])), Err(error(4, 5)));
+        assert_eq!(parse_u32(&attr(vec![1, 0, 0])), Err(error(4, 3)));
     }
 
     #[test]
     fn test_parse_i32() {
-        assert_eq!(parse_i32(&vec![1, 0, 0, 0]), 1 as i32);
+        assert_eq!(parse_i32(&attr(vec![1, 0, 0, 0])), Ok(1i32));
     }
 
     #[test]
-    #[should_panic]
-    fn test_parse_i32_should_panic() {
-        assert_eq!(parse_i32(&vec![1, 0, 0, 0, 0]), 1 as i32);
-        assert_eq!(parse_i32(&vec![1, 0, 0]), 1 as i32);
+    fn test_parse_i32_wrong_length() {
+        assert_eq!(parse_i32(&attr(vec![1, 0, 0, 0, 0])), Err(error(4, 5)));
+        assert_eq!(parse_i32(&attr(vec![1, 0, 0])), Err(error(4, 3)));
     }
 
     #[test]
     fn test_parse_u64() {
-        assert_eq!(parse_u64(&vec![1, 0, 0, 0, 0, 0, 0, 0]), 1 as u64);
+        assert_eq!(parse_u64(&attr(vec![1, 0, 0, 0, 0, 0, 0, 0])), Ok(1u64));
+    }
+
+    #[test]
+    fn test_parse_u64_wrong_length() {
+        assert_eq!(parse_u64(&attr(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])), Err(error(8, 9)));
+        assert_eq!(parse_u64(&attr(vec![1, 0, 0])), Err(error(8, 3)));
     }
 
     #[test]
-    #[should_panic]
-    fn test_parse_u64_should_panic() {
-        assert_eq!(parse_u64(&vec![1, 0, 0, 0, 0, 0, 0, 0, 0]), 1 as u64);
-        assert_eq!(parse_u64(&vec![1, 0, 0]), 1 as u64);
+    fn test_attr_error_display() {
+        assert_eq!(
+            error(4, 3).to_string(),
+            "invalid attribute AttrIfindex: expected 4 bytes, got 3"
+        );
     }
 }
@@ -0,0 +1,397 @@
+//! Record and replay nl80211 traffic as pcap files
+//!
+//! Captures use the netlink link type (LINKTYPE_NETLINK, as produced by the nlmon device), so they
+//! can be opened with Wireshark and its netlink/nl80211 dissectors.
+
+use crate::socket::{NlMessage, Transport};
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// pcap link type of netlink captures
+pub const LINKTYPE_NETLINK: u32 = 253;
+
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAP_SNAPLEN: u32 = 262_144;
+const COOKED_HEADER_LEN: usize = 16;
+const ARPHRD_NETLINK: u16 = 824;
+const NETLINK_GENERIC: u16 = 16;
+const PACKET_HOST: u16 = 0;
+const PACKET_OUTGOING: u16 = 4;
+const PACKET_USER: u16 = 6;
+const PACKET_KERNEL: u16 = 7;
+
+/// Direction of a captured datagram, seen from the application
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    /// Sent to the kernel
+    Sent,
+    /// Received from the kernel
+    Received,
+}
+
+/// A datagram read from a capture
+#[derive(Clone, Debug, PartialEq)]
+pub struct PcapRecord {
+    pub direction: Direction,
+    /// Capture time since the Unix epoch
+    pub timestamp: Duration,
+    /// Netlink messages, without the capture pseudo header
+    pub data: Vec<u8>,
+}
+
+impl PcapRecord {
+    /// Netlink messages carried by this datagram
+    pub fn messages(&self) -> Vec<NlMessage> {
+        NlMessage::split(&self.data)
+    }
+}
+
+/// Write netlink datagrams to a pcap capture
+pub struct PcapWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Write the pcap file header
+    pub fn new(mut writer: W) -> io::Result<PcapWriter<W>> {
+        let mut header = Vec::with_capacity(24);
+        header.extend_from_slice(&PCAP_MAGIC.to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&4u16.to_le_bytes());
+        header.extend_from_slice(&0i32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&PCAP_SNAPLEN.to_le_bytes());
+        header.extend_from_slice(&LINKTYPE_NETLINK.to_le_bytes());
+        writer.write_all(&header)?;
+
+        Ok(PcapWriter { writer })
+    }
+
+    /// Append a datagram stamped with the current time
+    pub fn write_datagram(&mut self, direction: Direction, data: &[u8]) -> io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.write_record(&PcapRecord {
+            direction,
+            timestamp,
+            data: data.to_vec(),
+        })
+    }
+
+    /// Append a record
+    pub fn write_record(&mut self, record: &PcapRecord) -> io::Result<()> {
+        let len = (COOKED_HEADER_LEN + record.data.len()) as u32;
+        let pkttype = match record.direction {
+            Direction::Sent => PACKET_USER,
+            Direction::Received => PACKET_KERNEL,
+        };
+
+        let mut packet = Vec::with_capacity(16 + len as usize);
+        packet.extend_from_slice(&(record.timestamp.as_secs() as u32).to_le_bytes());
+        packet.extend_from_slice(&record.timestamp.subsec_micros().to_le_bytes());
+        packet.extend_from_slice(&len.to_le_bytes());
+        packet.extend_from_slice(&len.to_le_bytes());
+        // Linux cooked header, in network byte order
+        packet.extend_from_slice(&pkttype.to_be_bytes());
+        packet.extend_from_slice(&ARPHRD_NETLINK.to_be_bytes());
+        packet.extend_from_slice(&[0; 10]);
+        packet.extend_from_slice(&NETLINK_GENERIC.to_be_bytes());
+        packet.extend_from_slice(&record.data);
+
+        self.writer.write_all(&packet)?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Read the records of a netlink pcap capture
+pub struct PcapReader<R: Read> {
+    reader: R,
+    swapped: bool,
+    nanos: bool,
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Read and check the pcap file header
+    pub fn new(mut reader: R) -> io::Result<PcapReader<R>> {
+        let mut header = [0; 24];
+        reader.read_exact(&mut header)?;
+
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let (swapped, nanos) = match magic {
+            PCAP_MAGIC => (false, false),
+            PCAP_MAGIC_NANOS => (false, true),
+            _ if magic.swap_bytes() == PCAP_MAGIC => (true, false),
+            _ if magic.swap_bytes() == PCAP_MAGIC_NANOS => (true, true),
+            _ => return Err(invalid_data("not a pcap file")),
+        };
+
+        let pcap = PcapReader {
+            reader,
+            swapped,
+            nanos,
+        };
+
+        if pcap.read_u32(&header[20..24]) != LINKTYPE_NETLINK {
+            return Err(invalid_data("not a netlink capture"));
+        }
+
+        Ok(pcap)
+    }
+
+    fn read_u32(&self, bytes: &[u8]) -> u32 {
+        let value = u32::from_le_bytes(bytes.try_into().unwrap());
+        if self.swapped {
+            value.swap_bytes()
+        } else {
+            value
+        }
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<PcapRecord>> {
+        let mut header = [0; 16];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        let secs = self.read_u32(&header[0..4]);
+        let fraction = self.read_u32(&header[4..8]);
+        let incl_len = self.read_u32(&header[8..12]) as usize;
+        if incl_len < COOKED_HEADER_LEN || incl_len > PCAP_SNAPLEN as usize {
+            return Err(invalid_data("invalid record length"));
+        }
+
+        let mut packet = vec![0; incl_len];
+        self.reader.read_exact(&mut packet)?;
+
+        let pkttype = u16::from_be_bytes([packet[0], packet[1]]);
+        // nlmon captures use PACKET_USER and PACKET_KERNEL, older tools the socket packet types
+        let direction = match pkttype {
+            PACKET_USER | PACKET_OUTGOING => Direction::Sent,
+            PACKET_KERNEL | PACKET_HOST => Direction::Received,
+            _ => Direction::Received,
+        };
+
+        let timestamp = if self.nanos {
+            Duration::new(secs as u64, fraction)
+        } else {
+            Duration::from_secs(secs as u64) + Duration::from_micros(fraction as u64)
+        };
+
+        Ok(Some(PcapRecord {
+            direction,
+            timestamp,
+            data: packet.split_off(COOKED_HEADER_LEN),
+        }))
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = io::Result<PcapRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Transport replaying the datagrams received in a capture
+///
+/// Requests sent by the socket are matched with the requests of the capture, and replies get the
+/// sequence number the socket expects, so every socket method runs unchanged against a capture.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nl80211::{PcapPlayer, Socket};
+/// # fn main() -> Result<(), nl80211::Nl80211Error> {
+/// let player = PcapPlayer::open("interfaces.pcap")?;
+/// let family_id = player.family_id().unwrap_or_default();
+///
+/// let interfaces = Socket::with_transport(player, family_id).get_interfaces_info()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PcapPlayer {
+    records: VecDeque<PcapRecord>,
+    recorded_seq: Option<u32>,
+    seq: u32,
+}
+
+impl PcapPlayer {
+    /// Load a capture file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<PcapPlayer> {
+        PcapPlayer::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Load a capture
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<PcapPlayer> {
+        let records = PcapReader::new(reader)?.collect::<io::Result<VecDeque<_>>>()?;
+
+        Ok(PcapPlayer {
+            records,
+            recorded_seq: None,
+            seq: 0,
+        })
+    }
+
+    /// nl80211 family id used when the capture was made
+    pub fn family_id(&self) -> Option<u16> {
+        self.records
+            .iter()
+            .filter(|record| record.direction == Direction::Sent)
+            .flat_map(|record| record.messages())
+            .map(|message| message.nl_type)
+            .next()
+    }
+
+    /// Messages received in the capture, to feed parsers directly
+    pub fn received_messages(&self) -> Vec<NlMessage> {
+        self.records
+            .iter()
+            .filter(|record| record.direction == Direction::Received)
+            .flat_map(|record| record.messages())
+            .collect()
+    }
+}
+
+impl Transport for PcapPlayer {
+    fn send(&mut self, buf: &[u8]) -> io::Result<()> {
+        let sent = NlMessage::split(buf);
+        self.seq = sent.first().map(|message| message.nl_seq).unwrap_or(0);
+
+        // Skip to the matching request of the capture
+        while let Some(record) = self.records.pop_front() {
+            if record.direction == Direction::Sent {
+                self.recorded_seq = record.messages().first().map(|message| message.nl_seq);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn recv(&mut self) -> io::Result<Vec<u8>> {
+        match self.records.front() {
+            Some(record) if record.direction == Direction::Received => (),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "no more replies in capture",
+                ))
+            }
+        }
+
+        let record = self.records.pop_front().unwrap();
+        let mut datagram = Vec::with_capacity(record.data.len());
+        for mut message in record.messages() {
+            if Some(message.nl_seq) == self.recorded_seq {
+                message.nl_seq = self.seq;
+            }
+            datagram.extend(message.to_bytes());
+        }
+
+        Ok(datagram)
+    }
+}
+
+#[cfg(test)]
+mod test_pcap {
+    use super::*;
+    use crate::attr::Nl80211Attr;
+    use crate::cmd::Nl80211Cmd;
+    use crate::socket::Socket;
+    use neli::nlattr::Nlattr;
+
+    fn capture() -> Vec<u8> {
+        let mut request = NlMessage::genl(28, Nl80211Cmd::CmdGetInterface, vec![]).unwrap();
+        request.nl_seq = 1234;
+
+        let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrIfindex, vec![3, 0, 0, 0]).unwrap()];
+        let mut reply = NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs).unwrap();
+        reply.nl_seq = 1234;
+        let mut done = NlMessage::done();
+        done.nl_seq = 1234;
+
+        let mut writer = PcapWriter::new(Vec::new()).unwrap();
+        writer
+            .write_datagram(Direction::Sent, &request.to_bytes())
+            .unwrap();
+        writer
+            .write_datagram(Direction::Received, &reply.to_bytes())
+            .unwrap();
+        writer
+            .write_datagram(Direction::Received, &done.to_bytes())
+            .unwrap();
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_read_write() {
+        let capture = capture();
+        assert_eq!(&capture[20..24], &[253, 0, 0, 0]);
+        // Cooked header of the first record
+        assert_eq!(&capture[40..44], &[0, 6, 3, 56]);
+
+        let records = PcapReader::new(&capture[..])
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].direction, Direction::Sent);
+        assert_eq!(records[1].direction, Direction::Received);
+        assert_eq!(
+            records[2].messages(),
+            vec![{
+                let mut done = NlMessage::done();
+                done.nl_seq = 1234;
+                done
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_direction() {
+        let directions = [
+            (PACKET_USER, Direction::Sent),
+            (PACKET_OUTGOING, Direction::Sent),
+            (PACKET_KERNEL, Direction::Received),
+            (PACKET_HOST, Direction::Received),
+        ];
+
+        for &(pkttype, direction) in directions.iter() {
+            let mut capture = capture();
+            capture[40..42].copy_from_slice(&pkttype.to_be_bytes());
+            let record = PcapReader::new(&capture[..]).unwrap().next().unwrap().unwrap();
+            assert_eq!(record.direction, direction);
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let player = PcapPlayer::from_reader(&capture()[..]).unwrap();
+        assert_eq!(player.family_id(), Some(28));
+        assert_eq!(player.received_messages().len(), 2);
+
+        let interfaces = Socket::with_transport(player, 28)
+            .get_interfaces_info()
+            .unwrap();
+        assert_eq!(interfaces.len(), 1);
+        assert_eq!(interfaces[0].ifindex, 3);
+    }
+}
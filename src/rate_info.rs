@@ -0,0 +1,205 @@
+use crate::attr::Nl80211RateInfo;
+use crate::error::AttrError;
+use crate::parse_attr::{parse_u16, parse_u32, parse_u8};
+use neli::nlattr::AttrHandle;
+use std::fmt;
+
+/// Channel width used to send a frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateWidth {
+    Mhz5,
+    Mhz10,
+    #[default]
+    Mhz20,
+    Mhz40,
+    Mhz80,
+    Mhz80p80,
+    Mhz160,
+}
+
+impl fmt::Display for RateWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = match self {
+            RateWidth::Mhz5 => "5MHz",
+            RateWidth::Mhz10 => "10MHz",
+            RateWidth::Mhz20 => "20MHz",
+            RateWidth::Mhz40 => "40MHz",
+            RateWidth::Mhz80 => "80MHz",
+            RateWidth::Mhz80p80 => "80P80MHz",
+            RateWidth::Mhz160 => "160MHz",
+        };
+        write!(f, "{}", width)
+    }
+}
+
+/// Bitrate of the frames sent to or received from a station
+///
+/// Decoded from the `Nl80211RateInfo` attributes nested in StaInfoTxBitrate and StaInfoRxBitrate.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateInfo {
+    /// Total bitrate in units of 100 kbit/s
+    pub bitrate: Option<u32>,
+    /// HT MCS index
+    pub mcs: Option<u8>,
+    /// VHT MCS index
+    pub vht_mcs: Option<u8>,
+    /// VHT number of spatial streams
+    pub vht_nss: Option<u8>,
+    /// HE MCS index
+    pub he_mcs: Option<u8>,
+    /// HE number of spatial streams
+    pub he_nss: Option<u8>,
+    /// HE guard interval (nl80211_he_gi: 0.8, 1.6 or 3.2 usec)
+    pub he_gi: Option<u8>,
+    /// HE dual carrier modulation
+    pub he_dcm: Option<u8>,
+    /// HE resource unit allocation (nl80211_he_ru_alloc)
+    pub he_ru_alloc: Option<u8>,
+    /// 400ns guard interval
+    pub short_gi: bool,
+    /// Channel width
+    pub width: RateWidth,
+}
+
+impl RateInfo {
+    /// Decode the attributes nested in StaInfoTxBitrate or StaInfoRxBitrate
+    pub fn parse(handle: AttrHandle<Nl80211RateInfo>) -> Result<RateInfo, AttrError> {
+        let mut rate_info = RateInfo::default();
+        let mut bitrate16 = None;
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211RateInfo::RateInfoBitrate32 => rate_info.bitrate = Some(parse_u32(attr)?),
+                Nl80211RateInfo::RateInfoBitrate => bitrate16 = Some(parse_u16(attr)? as u32),
+                Nl80211RateInfo::RateInfoMcs => rate_info.mcs = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoVhtMcs => rate_info.vht_mcs = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoVhtNss => rate_info.vht_nss = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoHeMcs => rate_info.he_mcs = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoHeNss => rate_info.he_nss = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoHeGi => rate_info.he_gi = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoHeDcm => rate_info.he_dcm = Some(parse_u8(attr)?),
+                Nl80211RateInfo::RateInfoHeRuAlloc => {
+                    rate_info.he_ru_alloc = Some(parse_u8(attr)?)
+                }
+                Nl80211RateInfo::RateInfoShortGi => rate_info.short_gi = true,
+                Nl80211RateInfo::RateInfo5MhzWidth => rate_info.width = RateWidth::Mhz5,
+                Nl80211RateInfo::RateInfo10MhzWidth => rate_info.width = RateWidth::Mhz10,
+                Nl80211RateInfo::RateInfo40MhzWidth => rate_info.width = RateWidth::Mhz40,
+                Nl80211RateInfo::RateInfo80MhzWidth => rate_info.width = RateWidth::Mhz80,
+                Nl80211RateInfo::RateInfo80p80MhzWidth => rate_info.width = RateWidth::Mhz80p80,
+                Nl80211RateInfo::RateInfo160MhzWidth => rate_info.width = RateWidth::Mhz160,
+                _ => (),
+            }
+        }
+
+        // The 16 bits bitrate is only sent for compatibility, and not at all when it overflows
+        rate_info.bitrate = rate_info.bitrate.or(bitrate16);
+        Ok(rate_info)
+    }
+
+    /// Total bitrate in MBit/s
+    pub fn mbit_per_sec(&self) -> Option<f64> {
+        self.bitrate.map(|bitrate| bitrate as f64 / 10.0)
+    }
+}
+
+impl fmt::Display for RateInfo {
+    /// Same format as iw, e.g. "866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bitrate {
+            Some(bitrate) if bitrate > 0 => write!(f, "{}.{} MBit/s", bitrate / 10, bitrate % 10)?,
+            _ => write!(f, "(unknown)")?,
+        }
+
+        if let Some(mcs) = self.mcs {
+            write!(f, " MCS {}", mcs)?;
+        }
+        if let Some(vht_mcs) = self.vht_mcs {
+            write!(f, " VHT-MCS {}", vht_mcs)?;
+        }
+        if self.width != RateWidth::Mhz20 {
+            write!(f, " {}", self.width)?;
+        }
+        if self.short_gi {
+            write!(f, " short GI")?;
+        }
+        if let Some(vht_nss) = self.vht_nss {
+            write!(f, " VHT-NSS {}", vht_nss)?;
+        }
+        if let Some(he_mcs) = self.he_mcs {
+            write!(f, " HE-MCS {}", he_mcs)?;
+        }
+        if let Some(he_nss) = self.he_nss {
+            write!(f, " HE-NSS {}", he_nss)?;
+        }
+        if let Some(he_gi) = self.he_gi {
+            write!(f, " HE-GI {}", he_gi)?;
+        }
+        if let Some(he_dcm) = self.he_dcm {
+            write!(f, " HE-DCM {}", he_dcm)?;
+        }
+        if let Some(he_ru_alloc) = self.he_ru_alloc {
+            write!(f, " HE-RU-ALLOC {}", he_ru_alloc)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_rate_info {
+    use super::*;
+    use neli::nlattr::Nlattr;
+
+    #[test]
+    fn test_parse_vht() {
+        let attrs = vec![
+            (Nl80211RateInfo::RateInfoBitrate32, vec![219, 33, 0, 0]),
+            (Nl80211RateInfo::RateInfoBitrate, vec![219, 33]),
+            (Nl80211RateInfo::RateInfoVhtMcs, vec![9]),
+            (Nl80211RateInfo::RateInfoVhtNss, vec![2]),
+            (Nl80211RateInfo::RateInfo80MhzWidth, vec![]),
+            (Nl80211RateInfo::RateInfoShortGi, vec![]),
+        ];
+        let handle = attrs
+            .into_iter()
+            .map(|(nla_type, payload)| Nlattr::new(None, nla_type, payload).unwrap())
+            .collect();
+
+        let rate_info = RateInfo::parse(AttrHandle::Owned(handle)).unwrap();
+        assert_eq!(
+            rate_info,
+            RateInfo {
+                bitrate: Some(8667),
+                vht_mcs: Some(9),
+                vht_nss: Some(2),
+                short_gi: true,
+                width: RateWidth::Mhz80,
+                ..RateInfo::default()
+            }
+        );
+        assert_eq!(
+            rate_info.to_string(),
+            "866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2"
+        );
+    }
+
+    #[test]
+    fn test_pretty_format_he() {
+        let rate_info = RateInfo {
+            bitrate: Some(12010),
+            he_mcs: Some(11),
+            he_nss: Some(2),
+            he_gi: Some(0),
+            he_dcm: Some(0),
+            width: RateWidth::Mhz80,
+            ..RateInfo::default()
+        };
+
+        assert_eq!(
+            rate_info.to_string(),
+            "1201.0 MBit/s 80MHz HE-MCS 11 HE-NSS 2 HE-GI 0 HE-DCM 0"
+        );
+        assert_eq!(RateInfo::default().to_string(), "(unknown)");
+    }
+}
@@ -0,0 +1,469 @@
+use crate::attr::{Nl80211Attr, Nl80211SchedScanMatchAttr, Nl80211SchedScanPlan, Nl80211ScanFlags};
+use crate::error::Nl80211Error;
+use crate::socket::{ifindex_attr, mac_attr};
+use crate::types::{Dbm, MacAddr, Mhz, Ssid};
+use neli::err::NlError;
+use neli::nlattr::Nlattr;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// Time given to a scan to complete by default
+pub const DEFAULT_SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parameters of a scan started with [`Socket::trigger_scan`](struct.Socket.html#method.trigger_scan)
+///
+/// Without SSID the scan is passive, only beacons are listened to. Add the empty SSID to send
+/// wildcard probe requests, and the SSIDs of hidden networks to find them.
+///
+/// # Example
+///
+/// ```
+/// # use nl80211::{MacAddr, Mhz, Nl80211ScanFlags, ScanRequest, Ssid};
+/// let request = ScanRequest::new()
+///     .ssid(Ssid::default())
+///     .ssid("hidden-network")
+///     .frequency(Mhz(2412))
+///     .frequency(Mhz(5180))
+///     .flag(Nl80211ScanFlags::ScanFlagFlush)
+///     .random_mac(MacAddr([0x02, 0, 0, 0, 0, 0]), MacAddr([0xff, 0xff, 0xff, 0, 0, 0]));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanRequest {
+    ssids: Vec<Ssid>,
+    frequencies: Vec<Mhz>,
+    ies: Vec<u8>,
+    flags: Vec<Nl80211ScanFlags>,
+    /// Address and mask of the random MAC address
+    random_mac: Option<(MacAddr, MacAddr)>,
+    pub(crate) timeout: Duration,
+}
+
+impl Default for ScanRequest {
+    fn default() -> ScanRequest {
+        ScanRequest {
+            ssids: Vec::new(),
+            frequencies: Vec::new(),
+            ies: Vec::new(),
+            flags: Vec::new(),
+            random_mac: None,
+            timeout: DEFAULT_SCAN_TIMEOUT,
+        }
+    }
+}
+
+impl ScanRequest {
+    /// A passive scan of every supported channel
+    pub fn new() -> ScanRequest {
+        ScanRequest::default()
+    }
+
+    /// Probe for a SSID, the empty SSID is the wildcard
+    pub fn ssid<S: Into<Ssid>>(mut self, ssid: S) -> ScanRequest {
+        self.ssids.push(ssid.into());
+        self
+    }
+
+    /// Scan this frequency, every supported one is scanned when none is given
+    pub fn frequency(mut self, frequency: Mhz) -> ScanRequest {
+        self.frequencies.push(frequency);
+        self
+    }
+
+    /// Information elements added to the probe requests
+    pub fn ies(mut self, ies: Vec<u8>) -> ScanRequest {
+        self.ies = ies;
+        self
+    }
+
+    /// Add a scan flag, the driver must support it
+    pub fn flag(mut self, flag: Nl80211ScanFlags) -> ScanRequest {
+        if !self.flags.contains(&flag) {
+            self.flags.push(flag);
+        }
+        self
+    }
+
+    /// Send probe requests from a random address
+    ///
+    /// Bits set in `mask` are taken from `mac`, the other ones are randomized.
+    pub fn random_mac(mut self, mac: MacAddr, mask: MacAddr) -> ScanRequest {
+        self.random_mac = Some((mac, mask));
+        self.flag(Nl80211ScanFlags::ScanFlagRandomAddr)
+    }
+
+    /// How long to wait for the scan to complete, 10 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> ScanRequest {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Attributes of the CmdTriggerScan command
+    pub(crate) fn attrs(
+        &self,
+        ifindex: u32,
+    ) -> Result<Vec<Nlattr<Nl80211Attr, Vec<u8>>>, Nl80211Error> {
+        let mut attrs = vec![ifindex_attr(ifindex)?];
+
+        if !self.ssids.is_empty() {
+            let ssids = self.ssids.iter().map(|ssid| ssid.as_bytes().to_vec());
+            attrs.push(nested_attr(Nl80211Attr::AttrScanSsids, ssids)?);
+        }
+        if !self.frequencies.is_empty() {
+            let frequencies = self.frequencies.iter().map(|freq| freq.0.to_le_bytes().to_vec());
+            attrs.push(nested_attr(Nl80211Attr::AttrScanFrequencies, frequencies)?);
+        }
+        if !self.ies.is_empty() {
+            attrs.push(Nlattr::new(None, Nl80211Attr::AttrIe, self.ies.clone())?);
+        }
+        if let Some((mac, mask)) = self.random_mac {
+            attrs.push(mac_attr(mac)?);
+            attrs.push(Nlattr::new(None, Nl80211Attr::AttrMacMask, mask.as_bytes().to_vec())?);
+        }
+        if !self.flags.is_empty() {
+            let flags = self
+                .flags
+                .iter()
+                .fold(0u32, |flags, flag| flags | u16::from(flag.clone()) as u32);
+            let flags = flags.to_le_bytes().to_vec();
+            attrs.push(Nlattr::new(None, Nl80211Attr::AttrScanFlags, flags)?);
+        }
+
+        Ok(attrs)
+    }
+}
+
+/// Parameters of a scheduled scan, run by the device on its own
+///
+/// The device scans following its scan plans and reports the BSS matching one of the match sets
+/// with a [`Nl80211Event::SchedScanResults`](enum.Nl80211Event.html) event. Without match set
+/// every BSS found is reported. SSIDs, frequencies, IEs and flags are taken from a
+/// [`ScanRequest`](struct.ScanRequest.html), its timeout is ignored.
+///
+/// # Example
+///
+/// ```
+/// # use nl80211::{Dbm, MatchSet, ScanPlan, ScanRequest, SchedScanRequest};
+/// # use std::time::Duration;
+/// // Every 10 seconds for a minute, then every 5 minutes
+/// let request = SchedScanRequest::new(ScanRequest::new().ssid("home"))
+///     .match_set(MatchSet::ssid("home").rssi_threshold(Dbm(-80)))
+///     .plan(ScanPlan::new(Duration::from_secs(10)).iterations(6))
+///     .plan(ScanPlan::new(Duration::from_secs(300)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchedScanRequest {
+    scan: ScanRequest,
+    match_sets: Vec<MatchSet>,
+    plans: Vec<ScanPlan>,
+    delay: Option<Duration>,
+}
+
+/// BSS reported by a scheduled scan, with a SSID and a signal above a threshold
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchSet {
+    /// `None` matches every SSID
+    pub ssid: Option<Ssid>,
+    /// Minimum signal strength, `None` to use the one of the first match set
+    pub rssi_threshold: Option<Dbm>,
+}
+
+/// A step of a scheduled scan: scans separated by an interval, repeated a number of times
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScanPlan {
+    /// Time between two scans, in whole seconds
+    pub interval: Duration,
+    /// Number of scans of the plan, `None` to repeat them until the scheduled scan is stopped
+    pub iterations: Option<u32>,
+}
+
+impl SchedScanRequest {
+    /// A scheduled scan probing and scanning as `scan`
+    pub fn new(scan: ScanRequest) -> SchedScanRequest {
+        SchedScanRequest {
+            scan,
+            ..SchedScanRequest::default()
+        }
+    }
+
+    /// Report the BSS matching this set
+    pub fn match_set(mut self, match_set: MatchSet) -> SchedScanRequest {
+        self.match_sets.push(match_set);
+        self
+    }
+
+    /// Add a scan plan, run after the previous ones
+    ///
+    /// At least one plan is needed, and the last one must repeat forever while the others must
+    /// not, starting the scan fails otherwise.
+    pub fn plan(mut self, plan: ScanPlan) -> SchedScanRequest {
+        self.plans.push(plan);
+        self
+    }
+
+    /// Wait before the first scan, in whole seconds
+    pub fn delay(mut self, delay: Duration) -> SchedScanRequest {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Attributes of the CmdStartSchedScan command
+    pub(crate) fn attrs(
+        &self,
+        ifindex: u32,
+    ) -> Result<Vec<Nlattr<Nl80211Attr, Vec<u8>>>, Nl80211Error> {
+        let mut attrs = self.scan.attrs(ifindex)?;
+
+        if !self.match_sets.is_empty() {
+            let match_sets = self.match_sets.iter().map(MatchSet::to_bytes);
+            attrs.push(nested_attr(Nl80211Attr::AttrSchedScanMatch, match_sets)?);
+        }
+        match self.plans.split_last() {
+            None => return Err(NlError::new("No scan plan").into()),
+            Some((last, _)) if last.iterations.is_some() => {
+                return Err(NlError::new("The last scan plan must repeat forever").into());
+            }
+            Some((_, others)) if others.iter().any(|plan| plan.iterations.is_none()) => {
+                return Err(NlError::new("Only the last scan plan may repeat forever").into());
+            }
+            Some(_) => (),
+        }
+        let plans = self
+            .plans
+            .iter()
+            .map(ScanPlan::to_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        attrs.push(nested_attr(Nl80211Attr::AttrSchedScanPlans, plans.into_iter())?);
+        if let Some(delay) = self.delay {
+            let delay = whole_secs(delay, "scheduled scan delay")?.to_le_bytes().to_vec();
+            attrs.push(Nlattr::new(None, Nl80211Attr::AttrSchedScanDelay, delay)?);
+        }
+
+        Ok(attrs)
+    }
+}
+
+impl MatchSet {
+    /// Match a SSID
+    pub fn ssid<S: Into<Ssid>>(ssid: S) -> MatchSet {
+        MatchSet {
+            ssid: Some(ssid.into()),
+            rssi_threshold: None,
+        }
+    }
+
+    /// Match every SSID
+    pub fn any() -> MatchSet {
+        MatchSet::default()
+    }
+
+    /// Ignore the BSS received with a weaker signal
+    pub fn rssi_threshold(mut self, rssi: Dbm) -> MatchSet {
+        self.rssi_threshold = Some(rssi);
+        self
+    }
+
+    /// Attributes nested in an entry of AttrSchedScanMatch
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if let Some(ssid) = &self.ssid {
+            let nla_type = Nl80211SchedScanMatchAttr::SchedScanMatchAttrSsid.into();
+            put_attr(&mut bytes, nla_type, ssid.as_bytes());
+        }
+        if let Some(rssi) = self.rssi_threshold {
+            let nla_type = Nl80211SchedScanMatchAttr::SchedScanMatchAttrRssi.into();
+            put_attr(&mut bytes, nla_type, &rssi.0.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl ScanPlan {
+    /// Scans every `interval`, until the scheduled scan is stopped
+    pub fn new(interval: Duration) -> ScanPlan {
+        ScanPlan {
+            interval,
+            iterations: None,
+        }
+    }
+
+    /// Stop the plan after a number of scans
+    pub fn iterations(mut self, iterations: u32) -> ScanPlan {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// Attributes nested in an entry of AttrSchedScanPlans
+    fn to_bytes(&self) -> Result<Vec<u8>, Nl80211Error> {
+        let mut bytes = Vec::new();
+        let interval = whole_secs(self.interval, "scan plan interval")?;
+        if interval == 0 || self.iterations == Some(0) {
+            return Err(NlError::new("Empty scan plan").into());
+        }
+        put_attr(
+            &mut bytes,
+            Nl80211SchedScanPlan::SchedScanPlanInterval.into(),
+            &interval.to_le_bytes(),
+        );
+        if let Some(iterations) = self.iterations {
+            put_attr(
+                &mut bytes,
+                Nl80211SchedScanPlan::SchedScanPlanIterations.into(),
+                &iterations.to_le_bytes(),
+            );
+        }
+        Ok(bytes)
+    }
+}
+
+/// A duration sent to the kernel as a u32 number of seconds
+fn whole_secs(duration: Duration, what: &str) -> Result<u32, Nl80211Error> {
+    match u32::try_from(duration.as_secs()) {
+        Ok(secs) if duration.subsec_nanos() == 0 => Ok(secs),
+        _ => Err(NlError::new(&format!("Invalid {} {:?}", what, duration)).into()),
+    }
+}
+
+/// Append a netlink attribute to a buffer, padded to 4 bytes
+pub(crate) fn put_attr(buffer: &mut Vec<u8>, nla_type: u16, payload: &[u8]) {
+    buffer.extend_from_slice(&(4 + payload.len() as u16).to_le_bytes());
+    buffer.extend_from_slice(&nla_type.to_le_bytes());
+    buffer.extend_from_slice(payload);
+    buffer.resize((buffer.len() + 3) & !3, 0);
+}
+
+/// Attribute holding a list, entries are nested attributes numbered from 1
+pub(crate) fn nested_attr<I: Iterator<Item = Vec<u8>>>(
+    nla_type: Nl80211Attr,
+    entries: I,
+) -> Result<Nlattr<Nl80211Attr, Vec<u8>>, Nl80211Error> {
+    let mut payload = Vec::new();
+    for (index, entry) in entries.enumerate() {
+        put_attr(&mut payload, index as u16 + 1, &entry);
+    }
+    Ok(Nlattr::new(None, nla_type, payload)?)
+}
+
+#[cfg(test)]
+mod test_scan {
+    use super::*;
+
+    fn payload(attrs: &[Nlattr<Nl80211Attr, Vec<u8>>], nla_type: Nl80211Attr) -> Option<&[u8]> {
+        attrs
+            .iter()
+            .find(|attr| attr.nla_type == nla_type)
+            .map(|attr| attr.payload.as_slice())
+    }
+
+    #[test]
+    fn test_passive_scan() {
+        let attrs = ScanRequest::new().attrs(3).unwrap();
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(payload(&attrs, Nl80211Attr::AttrIfindex), Some(&[3, 0, 0, 0][..]));
+        assert_eq!(ScanRequest::new().timeout, DEFAULT_SCAN_TIMEOUT);
+    }
+
+    #[test]
+    fn test_scan_attrs() {
+        let attrs = ScanRequest::new()
+            .ssid(Ssid::default())
+            .ssid("hidden")
+            .frequency(Mhz(2412))
+            .ies(vec![221, 4, 0x00, 0x50, 0xf2, 0x04])
+            .flag(Nl80211ScanFlags::ScanFlagFlush)
+            .random_mac(MacAddr([2, 0, 0, 0, 0, 0]), MacAddr([0xff, 0xff, 0xff, 0, 0, 0]))
+            .flag(Nl80211ScanFlags::ScanFlagRandomAddr)
+            .attrs(3)
+            .unwrap();
+
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrScanSsids),
+            Some(&[4, 0, 1, 0, 10, 0, 2, 0, b'h', b'i', b'd', b'd', b'e', b'n', 0, 0][..])
+        );
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrScanFrequencies),
+            Some(&[8, 0, 1, 0, 0x6c, 0x09, 0, 0][..])
+        );
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrIe),
+            Some(&[221, 4, 0x00, 0x50, 0xf2, 0x04][..])
+        );
+        assert_eq!(payload(&attrs, Nl80211Attr::AttrMac), Some(&[2, 0, 0, 0, 0, 0][..]));
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrMacMask),
+            Some(&[0xff, 0xff, 0xff, 0, 0, 0][..])
+        );
+        // Flush and random address, set once
+        assert_eq!(payload(&attrs, Nl80211Attr::AttrScanFlags), Some(&[0x0a, 0, 0, 0][..]));
+    }
+
+    #[test]
+    fn test_sched_scan_attrs() {
+        let attrs = SchedScanRequest::new(ScanRequest::new().ssid("home"))
+            .match_set(MatchSet::ssid("home").rssi_threshold(Dbm(-80)))
+            .match_set(MatchSet::any())
+            .plan(ScanPlan::new(Duration::from_secs(10)).iterations(6))
+            .plan(ScanPlan::new(Duration::from_secs(300)))
+            .delay(Duration::from_secs(2))
+            .attrs(3)
+            .unwrap();
+
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrScanSsids),
+            Some(&[8, 0, 1, 0, b'h', b'o', b'm', b'e'][..])
+        );
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrSchedScanMatch),
+            Some(
+                &[
+                    20, 0, 1, 0, 8, 0, 1, 0, b'h', b'o', b'm', b'e', 8, 0, 2, 0, 0xb0, 0xff, 0xff,
+                    0xff, 4, 0, 2, 0,
+                ][..]
+            )
+        );
+        assert_eq!(
+            payload(&attrs, Nl80211Attr::AttrSchedScanPlans),
+            Some(
+                &[
+                    20, 0, 1, 0, 8, 0, 1, 0, 10, 0, 0, 0, 8, 0, 2, 0, 6, 0, 0, 0, 12, 0, 2, 0, 8,
+                    0, 1, 0, 44, 1, 0, 0,
+                ][..]
+            )
+        );
+        assert_eq!(payload(&attrs, Nl80211Attr::AttrSchedScanDelay), Some(&[2, 0, 0, 0][..]));
+    }
+
+    #[test]
+    fn test_invalid_sched_scan() {
+        let request = SchedScanRequest::new(ScanRequest::new());
+        let invalid = vec![
+            request.clone().plan(ScanPlan::new(Duration::from_millis(500))),
+            request.clone().plan(ScanPlan::new(Duration::from_millis(1500))),
+            request.clone().plan(ScanPlan::new(Duration::from_secs(1 << 32))),
+            request.clone().plan(ScanPlan::new(Duration::from_secs(10)).iterations(0)),
+            request.clone().plan(ScanPlan::new(Duration::from_secs(10)).iterations(6)),
+            request
+                .clone()
+                .plan(ScanPlan::new(Duration::from_secs(10)))
+                .plan(ScanPlan::new(Duration::from_secs(300))),
+            request
+                .clone()
+                .plan(ScanPlan::new(Duration::from_secs(10)))
+                .plan(ScanPlan::new(Duration::from_secs(300)).iterations(2)),
+            request.clone(),
+            request
+                .clone()
+                .plan(ScanPlan::new(Duration::from_secs(10)))
+                .delay(Duration::from_secs(1 << 32)),
+        ];
+
+        for request in invalid {
+            match request.attrs(3) {
+                Err(Nl80211Error::Netlink(_)) => (),
+                other => panic!("{:?} accepted: {:?}", request, other),
+            }
+        }
+
+        let request = request.plan(ScanPlan::new(Duration::from_secs(10)));
+        assert!(request.delay(Duration::from_secs(0)).attrs(3).is_ok());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::attr::Nl80211WpaVersions;
+use crate::ie::{Akm, Rsn};
+use std::fmt;
+
+/// Security of a BSS, summarized from its RSN and WPA elements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Security {
+    Open,
+    /// Opportunistic Wireless Encryption (Enhanced Open)
+    Owe,
+    Wep,
+    WpaPsk,
+    Wpa2Psk,
+    Wpa3Sae,
+    /// WPA3-SAE with WPA2-PSK allowed for older clients
+    Wpa2Wpa3Transition,
+    WpaEnterprise,
+    Wpa2Enterprise,
+    Wpa3Enterprise192,
+    /// Encrypted with AKM suites this crate doesn't know
+    Unknown,
+}
+
+impl Security {
+    /// Classify a BSS from the privacy bit of its capability field and its RSN and WPA elements
+    ///
+    /// The RSN element wins over the legacy WPA element when a BSS advertises both.
+    pub fn classify(privacy: bool, rsn: Option<&Rsn>, wpa: Option<&Rsn>) -> Security {
+        if let Some(rsn) = rsn {
+            let akms = rsn.akms();
+            let has = |f: fn(&Akm) -> bool| akms.iter().any(f);
+
+            if akms.contains(&Akm::Ieee8021xSuiteB192) {
+                return Security::Wpa3Enterprise192;
+            }
+            if has(Akm::is_sae) && has(Akm::is_psk) {
+                return Security::Wpa2Wpa3Transition;
+            }
+            if has(Akm::is_sae) {
+                return Security::Wpa3Sae;
+            }
+            if akms.contains(&Akm::Owe) {
+                return Security::Owe;
+            }
+            if has(Akm::is_psk) {
+                return Security::Wpa2Psk;
+            }
+            if has(Akm::is_ieee8021x) {
+                return Security::Wpa2Enterprise;
+            }
+        }
+
+        if let Some(wpa) = wpa {
+            let akms = wpa.akms();
+            if akms.contains(&Akm::Psk) {
+                return Security::WpaPsk;
+            }
+            if akms.contains(&Akm::Ieee8021x) {
+                return Security::WpaEnterprise;
+            }
+        }
+
+        match (privacy, rsn.or(wpa)) {
+            (_, Some(_)) => Security::Unknown,
+            (true, None) => Security::Wep,
+            (false, None) => Security::Open,
+        }
+    }
+
+    /// WPA version to connect with, `None` without WPA
+    pub fn wpa_version(&self) -> Option<Nl80211WpaVersions> {
+        match self {
+            Security::WpaPsk | Security::WpaEnterprise => Some(Nl80211WpaVersions::WpaVersion1),
+            Security::Wpa2Psk | Security::Wpa2Wpa3Transition | Security::Wpa2Enterprise => {
+                Some(Nl80211WpaVersions::WpaVersion2)
+            }
+            Security::Wpa3Sae | Security::Wpa3Enterprise192 | Security::Owe => {
+                Some(Nl80211WpaVersions::WpaVersion3)
+            }
+            Security::Open | Security::Wep | Security::Unknown => None,
+        }
+    }
+}
+
+impl fmt::Display for Security {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Security::Open => "Open",
+            Security::Owe => "OWE",
+            Security::Wep => "WEP",
+            Security::WpaPsk => "WPA-PSK",
+            Security::Wpa2Psk => "WPA2-PSK",
+            Security::Wpa3Sae => "WPA3-SAE",
+            Security::Wpa2Wpa3Transition => "WPA2/WPA3",
+            Security::WpaEnterprise => "WPA-Enterprise",
+            Security::Wpa2Enterprise => "WPA2-Enterprise",
+            Security::Wpa3Enterprise192 => "WPA3-Enterprise-192",
+            Security::Unknown => "Unknown",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[cfg(test)]
+mod test_security {
+    use super::*;
+
+    fn rsn(akm_suites: Vec<Akm>) -> Rsn {
+        Rsn {
+            version: 1,
+            akm_suites,
+            ..Rsn::default()
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let cases = vec![
+            (rsn(vec![Akm::Psk]), Security::Wpa2Psk),
+            (rsn(vec![Akm::FtPsk, Akm::PskSha256]), Security::Wpa2Psk),
+            (rsn(vec![Akm::Sae, Akm::FtSae]), Security::Wpa3Sae),
+            (rsn(vec![Akm::Psk, Akm::Sae]), Security::Wpa2Wpa3Transition),
+            (rsn(vec![Akm::Owe]), Security::Owe),
+            (rsn(vec![]), Security::Wpa2Enterprise),
+            (rsn(vec![Akm::Ieee8021xSuiteB192]), Security::Wpa3Enterprise192),
+            (rsn(vec![Akm::FilsSha256]), Security::Unknown),
+        ];
+
+        for (rsn, security) in cases {
+            assert_eq!(Security::classify(true, Some(&rsn), None), security);
+        }
+    }
+
+    #[test]
+    fn test_classify_legacy() {
+        let wpa = rsn(vec![Akm::Psk]);
+
+        assert_eq!(Security::classify(false, None, None), Security::Open);
+        assert_eq!(Security::classify(true, None, None), Security::Wep);
+        assert_eq!(Security::classify(true, None, Some(&wpa)), Security::WpaPsk);
+        assert_eq!(
+            Security::classify(true, Some(&rsn(vec![Akm::Psk])), Some(&wpa)),
+            Security::Wpa2Psk
+        );
+        assert_eq!(Security::WpaPsk.to_string(), "WPA-PSK");
+        assert_eq!(
+            Security::Wpa2Wpa3Transition.wpa_version(),
+            Some(Nl80211WpaVersions::WpaVersion2)
+        );
+    }
+}
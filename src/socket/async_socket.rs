@@ -0,0 +1,545 @@
+use crate::attr::Nl80211Attr;
+use crate::bss::Bss;
+use crate::cmd::Nl80211Cmd;
+use crate::consts::NL_80211_GENL_NAME;
+use crate::error::Nl80211Error;
+use crate::event::{MulticastGroup, Nl80211Event};
+use crate::interface::Interface;
+use crate::nl80211traits::ParseNlAttr;
+use crate::pcap::PcapWriter;
+use crate::scan::{ScanRequest, SchedScanRequest};
+use crate::socket::message::NlMessage;
+use crate::socket::netns::{self, Netns};
+use crate::socket::session::{Reply, Session};
+use crate::socket::transport::{NetlinkTransport, Transport};
+use crate::socket::{ifindex_attr, mac_attr, scan_outcome};
+use crate::station::Station;
+use crate::types::MacAddr;
+use crate::wiphy::Wiphy;
+use neli::consts::{NlFamily, NlmF};
+use neli::err::NlError;
+use neli::nlattr::Nlattr;
+use neli::socket::NlSocket;
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::unix::AsyncFd;
+
+/// An async nl80211 socket driven by tokio
+///
+/// Same requests as [`Socket`](struct.Socket.html), awaiting the kernel instead of blocking the
+/// thread. Replies are collected in a `Vec` rather than iterated, and events are received one at
+/// a time with [`recv_event`](#method.recv_event). Only available with the `async` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// # use nl80211::AsyncSocket;
+/// # async fn run() -> Result<(), nl80211::Nl80211Error> {
+///     let mut socket = AsyncSocket::connect()?;
+///     for interface in socket.get_interfaces_info().await? {
+///         println!("{}", interface);
+///     }
+/// #   Ok(())
+/// # }
+/// ```
+pub struct AsyncSocket<T: Transport + AsRawFd = NetlinkTransport> {
+    fd: AsyncFd<T>,
+    pub family_id: u16,
+    session: Session,
+}
+
+impl AsyncSocket {
+    /// Create a new async nl80211 socket with netlink
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn connect() -> Result<Self, Nl80211Error> {
+        let family_id = {
+            NlSocket::new(NlFamily::Generic, true)?
+                .resolve_genl_family(NL_80211_GENL_NAME)?
+        };
+
+        let mut transport = NetlinkTransport::connect()?;
+        transport.set_nonblocking(true)?;
+
+        Ok(AsyncSocket::with_transport(transport, family_id)?)
+    }
+
+    /// Create a new async nl80211 socket inside a network namespace
+    ///
+    /// See [`Socket::connect_in_netns`](struct.Socket.html#method.connect_in_netns).
+    pub fn connect_in_netns<P: AsRef<Path>>(path: P) -> Result<Self, Nl80211Error> {
+        let netns = File::open(path)?;
+        AsyncSocket::connect_in_netns_fd(netns.as_raw_fd())
+    }
+
+    /// Create a new async nl80211 socket inside the network namespace referred to by a file
+    /// descriptor
+    pub fn connect_in_netns_fd(netns: RawFd) -> Result<Self, Nl80211Error> {
+        netns::run_in(netns, AsyncSocket::connect)
+    }
+
+    /// Receive the events published by nl80211 on a multicast group
+    pub fn subscribe(&mut self, group: MulticastGroup) -> Result<(), Nl80211Error> {
+        let group_id = group.resolve()?;
+        self.add_membership(group_id)
+    }
+
+    /// Scan with an interface and return the BSS found
+    ///
+    /// See [`Socket::trigger_scan`](struct.Socket.html#method.trigger_scan). The timeout of the
+    /// request needs the time driver of the tokio runtime.
+    pub async fn trigger_scan(
+        &mut self,
+        ifindex: u32,
+        request: ScanRequest,
+    ) -> Result<Vec<Bss>, Nl80211Error> {
+        self.subscribe(MulticastGroup::Scan)?;
+        self.start_scan(ifindex, &request).await?;
+        self.wait_scan(ifindex, request.timeout).await
+    }
+}
+
+impl<T: Transport + AsRawFd> AsyncSocket<T> {
+    /// Create an async nl80211 socket on top of a transport
+    ///
+    /// The transport must be in nonblocking mode, its `send` and `recv` returning `WouldBlock`
+    /// when they would have to wait.
+    pub fn with_transport(transport: T, family_id: u16) -> io::Result<Self> {
+        // The transport owns its descriptor for as long as the AsyncFd holds it, the I/O safety
+        // concern behind the deprecation of AsyncFd::new in recent tokio releases
+        #[allow(deprecated)]
+        let fd = AsyncFd::new(transport)?;
+
+        Ok(AsyncSocket {
+            fd,
+            family_id,
+            session: Session::default(),
+        })
+    }
+
+    /// Transport used by this socket
+    pub fn transport(&self) -> &T {
+        self.fd.get_ref()
+    }
+
+    /// Record every datagram sent and received by this socket to a pcap capture
+    pub fn record<W: Write + Send + 'static>(&mut self, writer: W) -> Result<(), Nl80211Error> {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        self.session.record(Some(PcapWriter::new(writer)?));
+        Ok(())
+    }
+
+    /// Stop recording
+    pub fn stop_recording(&mut self) {
+        self.session.record(None);
+    }
+
+    /// Join a multicast group by id
+    pub fn add_membership(&mut self, group_id: u32) -> Result<(), Nl80211Error> {
+        self.fd.get_mut().add_membership(group_id)?;
+        Ok(())
+    }
+
+    /// Wait for the next multicast event
+    ///
+    /// Events received while waiting for replies are queued and returned first.
+    pub async fn recv_event(&mut self) -> Result<Nl80211Event, Nl80211Error> {
+        loop {
+            if let Some(message) = self.session.next_event() {
+                return Nl80211Event::from_genlmsghdr(&message.genlmsghdr()?);
+            }
+            self.recv_datagram().await?;
+        }
+    }
+
+    /// Get information for all your wifi interfaces
+    pub async fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, Nl80211Error> {
+        self.dump_all(Nl80211Cmd::CmdGetInterface, vec![]).await
+    }
+
+    /// Get every wiphy with its bands and capabilities
+    pub async fn get_wiphys(&mut self) -> Result<Vec<Wiphy>, Nl80211Error> {
+        let split = Nlattr::new(None, Nl80211Attr::AttrSplitWiphyDump, Vec::<u8>::new())?;
+        let parts = self.dump_all(Nl80211Cmd::CmdGetWiphy, vec![split]).await?;
+        Ok(Wiphy::merge_split_dump(parts))
+    }
+
+    /// Get access point information for a specific interface
+    pub async fn get_station_info(&mut self, ifindex: u32) -> Result<Station, Nl80211Error> {
+        let stations = self.get_stations(ifindex).await?;
+        Ok(stations.into_iter().next().unwrap_or_default())
+    }
+
+    /// Get the first BSS found by the last scan of an interface
+    pub async fn get_bss_info(&mut self, ifindex: u32) -> Result<Bss, Nl80211Error> {
+        let bss = self.get_scan_results(ifindex).await?;
+        Ok(bss.into_iter().next().unwrap_or_default())
+    }
+
+    /// Get every station known by an interface
+    pub async fn get_stations(&mut self, ifindex: u32) -> Result<Vec<Station>, Nl80211Error> {
+        self.dump_all(Nl80211Cmd::CmdGetStation, vec![ifindex_attr(ifindex)?]).await
+    }
+
+    /// Get a station by MAC address
+    pub async fn get_station(
+        &mut self,
+        ifindex: u32,
+        mac: MacAddr,
+    ) -> Result<Station, Nl80211Error> {
+        let attrs = vec![ifindex_attr(ifindex)?, mac_attr(mac)?];
+
+        self.request(Nl80211Cmd::CmdGetStation, attrs)
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| Err(NlError::new("No station returned by the kernel").into()))
+    }
+
+    /// Get every BSS found by the scans of an interface
+    pub async fn get_scan_results(&mut self, ifindex: u32) -> Result<Vec<Bss>, Nl80211Error> {
+        self.dump_all(Nl80211Cmd::CmdGetScan, vec![ifindex_attr(ifindex)?]).await
+    }
+
+    /// Start a scan without waiting for its end
+    pub async fn start_scan(
+        &mut self,
+        ifindex: u32,
+        request: &ScanRequest,
+    ) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdTriggerScan, request.attrs(ifindex)?)
+            .await?
+            .into_iter()
+            .collect()
+    }
+
+    /// Abort the scan running on an interface
+    pub async fn abort_scan(&mut self, ifindex: u32) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdAbortScan, vec![ifindex_attr(ifindex)?])
+            .await?
+            .into_iter()
+            .collect()
+    }
+
+    /// Start a scheduled scan, run by the device until it is stopped
+    pub async fn start_sched_scan(
+        &mut self,
+        ifindex: u32,
+        request: &SchedScanRequest,
+    ) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdStartSchedScan, request.attrs(ifindex)?)
+            .await?
+            .into_iter()
+            .collect()
+    }
+
+    /// Stop the scheduled scan of an interface
+    pub async fn stop_sched_scan(&mut self, ifindex: u32) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdStopSchedScan, vec![ifindex_attr(ifindex)?])
+            .await?
+            .into_iter()
+            .collect()
+    }
+
+    /// Wait for the end of the scan of an interface, then get the BSS found
+    ///
+    /// The timeout needs the time driver of the tokio runtime.
+    pub async fn wait_scan(
+        &mut self,
+        ifindex: u32,
+        timeout: Duration,
+    ) -> Result<Vec<Bss>, Nl80211Error> {
+        match tokio::time::timeout(timeout, self.wait_scan_event(ifindex)).await {
+            Ok(outcome) => outcome?,
+            Err(_elapsed) => {
+                return Err(Nl80211Error::Timeout {
+                    cmd: Nl80211Cmd::CmdTriggerScan,
+                })
+            }
+        }
+
+        self.get_scan_results(ifindex).await
+    }
+
+    async fn wait_scan_event(&mut self, ifindex: u32) -> Result<(), Nl80211Error> {
+        loop {
+            let event = match self.recv_event().await {
+                Err(Nl80211Error::InvalidAttribute(_)) => continue,
+                event => event?,
+            };
+
+            if let Some(outcome) = scan_outcome(&event, ifindex) {
+                return outcome;
+            }
+        }
+    }
+
+    /// Move a wiphy and its interfaces to another network namespace
+    pub async fn set_wiphy_netns(&mut self, wiphy: u32, netns: Netns) -> Result<(), Nl80211Error> {
+        let attrs = netns::wiphy_netns_attrs(wiphy, netns)?;
+        self.request::<()>(Nl80211Cmd::CmdSetWiphyNetns, attrs)
+            .await?
+            .into_iter()
+            .collect()
+    }
+
+    /// Send a command and ask the kernel to acknowledge it
+    ///
+    /// Returns the messages sent back before the ACK, parsed as `P`, see
+    /// [`Socket::request`](struct.Socket.html#method.request). Replies whose attributes can't be
+    /// decoded are returned as errors next to the others, so that they can be skipped.
+    pub async fn request<P: ParseNlAttr + Default>(
+        &mut self,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Vec<Result<P, Nl80211Error>>, Nl80211Error> {
+        let flags = vec![NlmF::Request, NlmF::Ack];
+        let datagram = self.session.encode_request(self.family_id, cmd, flags, attrs)?;
+        self.send(&datagram).await?;
+        self.collect_replies().await
+    }
+
+    /// Send a dump command and collect every message, parsed as `P`
+    ///
+    /// Like [`AsyncSocket::request`](#method.request), replies which can't be decoded are returned
+    /// as errors. Interrupted dumps fail with
+    /// [`Nl80211Error::DumpInterrupted`](enum.Nl80211Error.html), use
+    /// [`AsyncSocket::dump_all`](#method.dump_all) to restart them.
+    pub async fn dump<P: ParseNlAttr + Default>(
+        &mut self,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Vec<Result<P, Nl80211Error>>, Nl80211Error> {
+        let flags = vec![NlmF::Request, NlmF::Dump];
+        let datagram = self.session.encode_request(self.family_id, cmd, flags, attrs)?;
+        self.send(&datagram).await?;
+        self.collect_replies().await
+    }
+
+    /// Send a dump command and collect every message
+    ///
+    /// Interrupted dumps are restarted like with [`Socket::dump_all`](struct.Socket.html#method.dump_all),
+    /// and messages which can't be decoded are skipped.
+    pub async fn dump_all<P: ParseNlAttr + Default>(
+        &mut self,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Vec<P>, Nl80211Error> {
+        let mut result = self.dump(cmd.clone(), attrs).await;
+        let mut attempts = 1;
+
+        while let Err(Nl80211Error::DumpInterrupted { .. }) = result {
+            if attempts > self.session.dump_retries {
+                return Err(Nl80211Error::DumpInterrupted { cmd, attempts });
+            }
+
+            let datagram = self.session.encode_retry()?;
+            self.send(&datagram).await?;
+            result = self.collect_replies().await;
+            attempts += 1;
+        }
+
+        Ok(result?.into_iter().filter_map(Result::ok).collect())
+    }
+
+    /// Set how many times an interrupted dump is restarted, 3 by default
+    pub fn set_dump_retries(&mut self, retries: u32) {
+        self.session.dump_retries = retries;
+    }
+
+    /// Parse the replies to the last request until the ACK or the end of the dump
+    ///
+    /// A reply which can't be parsed doesn't stop the collection, the whole answer is consumed.
+    async fn collect_replies<P: ParseNlAttr + Default>(
+        &mut self,
+    ) -> Result<Vec<Result<P, Nl80211Error>>, Nl80211Error> {
+        let mut replies = Vec::new();
+        loop {
+            let message = self.recv().await?;
+            match self.session.decode_reply(&message)? {
+                Reply::Data(genlhdr) => replies.push(P::default().parse(genlhdr.get_attr_handle())),
+                Reply::Ack | Reply::Done => return Ok(replies),
+            }
+        }
+    }
+
+    /// Send a datagram once the transport is writable
+    async fn send(&mut self, datagram: &[u8]) -> Result<(), Nl80211Error> {
+        loop {
+            let mut guard = self.fd.writable_mut().await?;
+            match guard.try_io(|fd| fd.get_mut().send(datagram)) {
+                Ok(result) => return Ok(result?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Wait for the next message answering the last request
+    async fn recv(&mut self) -> Result<NlMessage, Nl80211Error> {
+        loop {
+            if let Some(message) = self.session.next_reply() {
+                return Ok(message);
+            }
+            self.recv_datagram().await?;
+        }
+    }
+
+    /// Wait until the transport is readable and queue the datagram received
+    async fn recv_datagram(&mut self) -> Result<(), Nl80211Error> {
+        let mut guard = self.fd.readable_mut().await?;
+        if let Ok(datagram) = guard.try_io(|fd| fd.get_mut().recv()) {
+            self.session.push_datagram(&datagram?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_async_socket {
+    use super::*;
+    use std::convert::TryInto;
+    use std::os::unix::io::RawFd;
+    use std::os::unix::net::UnixDatagram;
+
+    /// Nonblocking datagram socket standing for the kernel side of a netlink socket
+    struct PairTransport(UnixDatagram);
+
+    impl Transport for PairTransport {
+        fn send(&mut self, buf: &[u8]) -> io::Result<()> {
+            self.0.send(buf).map(|_| ())
+        }
+
+        fn recv(&mut self) -> io::Result<Vec<u8>> {
+            let mut buf = vec![0; 8192];
+            let len = self.0.recv(&mut buf)?;
+            buf.truncate(len);
+            Ok(buf)
+        }
+    }
+
+    impl AsRawFd for PairTransport {
+        fn as_raw_fd(&self) -> RawFd {
+            self.0.as_raw_fd()
+        }
+    }
+
+    /// Answer each request with a list of messages, replies get the sequence number of the request
+    /// and events keep theirs
+    fn spawn_kernel(
+        kernel: UnixDatagram,
+        script: Vec<Vec<(NlMessage, bool)>>,
+    ) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            for answer in script {
+                let mut request = vec![0; 8192];
+                let len = kernel.recv(&mut request).unwrap();
+                let seq = u32::from_le_bytes(request[8..12].try_into().unwrap());
+                assert!(len > 16);
+
+                for (mut message, is_reply) in answer {
+                    if is_reply {
+                        message.nl_seq = seq;
+                    }
+                    kernel.send(&message.to_bytes()).unwrap();
+                }
+            }
+        })
+    }
+
+    fn connect(
+        script: Vec<Vec<(NlMessage, bool)>>,
+    ) -> (AsyncSocket<PairTransport>, std::thread::JoinHandle<()>) {
+        let (local, kernel) = UnixDatagram::pair().unwrap();
+        local.set_nonblocking(true).unwrap();
+        let socket = AsyncSocket::with_transport(PairTransport(local), 28).unwrap();
+        (socket, spawn_kernel(kernel, script))
+    }
+
+    fn interface(ifindex: Vec<u8>) -> (NlMessage, bool) {
+        let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrIfindex, ifindex).unwrap()];
+        (NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs).unwrap(), true)
+    }
+
+    fn scan_event(cmd: Nl80211Cmd, ifindex: u32) -> (NlMessage, bool) {
+        let attrs = vec![ifindex_attr(ifindex).unwrap()];
+        (NlMessage::genl(28, cmd, attrs).unwrap(), false)
+    }
+
+    #[tokio::test]
+    async fn test_get_interfaces_info() {
+        let script = vec![vec![interface(vec![3, 0, 0, 0]), (NlMessage::done(), true)]];
+        let (mut socket, kernel) = connect(script);
+
+        let interfaces = socket.get_interfaces_info().await.unwrap();
+        kernel.join().unwrap();
+
+        assert_eq!(interfaces.len(), 1);
+        assert_eq!(interfaces[0].ifindex, 3);
+    }
+
+    #[tokio::test]
+    async fn test_skip_invalid_replies() {
+        let dump = vec![
+            interface(vec![3, 0, 0, 0]),
+            interface(vec![4, 0]),
+            interface(vec![5, 0, 0, 0]),
+            (NlMessage::done(), true),
+        ];
+        let (mut socket, kernel) = connect(vec![dump.clone(), dump]);
+
+        let replies = socket
+            .dump::<Interface>(Nl80211Cmd::CmdGetInterface, vec![])
+            .await
+            .unwrap();
+        assert_eq!(replies.len(), 3);
+        match &replies[1] {
+            Err(Nl80211Error::InvalidAttribute(error)) => assert_eq!(error.actual, 2),
+            other => panic!("unexpected {:?}", other.as_ref().map(|_| ())),
+        }
+        let indexes: Vec<_> = replies.into_iter().flatten().map(|i| i.ifindex).collect();
+        assert_eq!(indexes, vec![3, 5]);
+
+        // The whole dump is consumed, the next request gets its own replies without the invalid one
+        let interfaces = socket.get_interfaces_info().await.unwrap();
+        assert_eq!(interfaces.iter().map(|i| i.ifindex).collect::<Vec<_>>(), vec![3, 5]);
+        kernel.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan() {
+        let script = vec![
+            vec![
+                (NlMessage::error(0), true),
+                scan_event(Nl80211Cmd::CmdNewScanResults, 4),
+                scan_event(Nl80211Cmd::CmdNewScanResults, 3),
+            ],
+            vec![(NlMessage::done(), true)],
+            vec![
+                (NlMessage::error(0), true),
+                scan_event(Nl80211Cmd::CmdScanAborted, 3),
+            ],
+            vec![(NlMessage::error(0), true)],
+        ];
+        let (mut socket, kernel) = connect(script);
+        let timeout = Duration::from_secs(5);
+
+        socket.start_scan(3, &ScanRequest::new()).await.unwrap();
+        assert!(socket.wait_scan(3, timeout).await.unwrap().is_empty());
+
+        socket.start_scan(3, &ScanRequest::new()).await.unwrap();
+        match socket.wait_scan(3, timeout).await {
+            Err(Nl80211Error::ScanAborted { ifindex: 3 }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        socket.start_scan(3, &ScanRequest::new()).await.unwrap();
+        match socket.wait_scan(3, Duration::from_millis(50)).await {
+            Err(Nl80211Error::Timeout { cmd }) => assert_eq!(cmd, Nl80211Cmd::CmdTriggerScan),
+            other => panic!("unexpected {:?}", other),
+        }
+        kernel.join().unwrap();
+    }
+}
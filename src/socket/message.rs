@@ -0,0 +1,142 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+use crate::consts::NL_80211_GENL_VERSION;
+use crate::error::Nl80211Error;
+use buffering::{StreamReadBuffer, StreamWriteBuffer};
+use neli::consts::Nlmsg;
+use neli::genl::Genlmsghdr;
+use neli::nlattr::Nlattr;
+use neli::Nl;
+use std::convert::TryInto;
+
+/// Length of the netlink message header (struct nlmsghdr)
+pub const NLMSG_HDRLEN: usize = 16;
+
+/// Flag set by the kernel on dump messages when the dumped data changed during the dump
+pub const NLM_F_DUMP_INTR: u16 = 0x10;
+
+/// A raw netlink message, as sent or received by a transport
+#[derive(Clone, Debug, PartialEq)]
+pub struct NlMessage {
+    /// Message type, the nl80211 family id or a netlink control type (error, done...)
+    pub nl_type: u16,
+    /// NLM_F_* flags
+    pub nl_flags: u16,
+    /// Sequence number
+    pub nl_seq: u32,
+    /// Port id of the sender
+    pub nl_pid: u32,
+    /// Generic netlink header and attributes, or nlmsgerr for error messages
+    pub nl_payload: Vec<u8>,
+}
+
+impl NlMessage {
+    /// Create a message with the given type and payload
+    pub fn new(nl_type: u16, nl_flags: u16, nl_payload: Vec<u8>) -> NlMessage {
+        NlMessage {
+            nl_type,
+            nl_flags,
+            nl_seq: 0,
+            nl_pid: 0,
+            nl_payload,
+        }
+    }
+
+    /// Create a nl80211 message carrying a command and its attributes
+    pub fn genl(
+        family_id: u16,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<NlMessage, Nl80211Error> {
+        let genlhdr = Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs)?;
+        let mut buffer = StreamWriteBuffer::new_growable(Some(genlhdr.asize()));
+        genlhdr.serialize(&mut buffer)?;
+
+        Ok(NlMessage::new(family_id, 0, buffer.as_ref().to_vec()))
+    }
+
+    /// Create the message ending a dump
+    pub fn done() -> NlMessage {
+        NlMessage::new(Nlmsg::Done.into(), 0, vec![0, 0, 0, 0])
+    }
+
+    /// Create an error message for errno (positive), or an ack when errno is 0
+    pub fn error(errno: i32) -> NlMessage {
+        let mut payload = (-errno).to_le_bytes().to_vec();
+        payload.extend_from_slice(&[0; NLMSG_HDRLEN]);
+        NlMessage::new(Nlmsg::Error.into(), 0, payload)
+    }
+
+    /// The kernel flagged this message as part of an inconsistent dump
+    pub fn is_dump_interrupted(&self) -> bool {
+        self.nl_flags & NLM_F_DUMP_INTR != 0
+    }
+
+    /// Deserialize the generic netlink header and attributes carried by this message
+    pub fn genlmsghdr(&self) -> Result<Genlmsghdr<Nl80211Cmd, Nl80211Attr>, Nl80211Error> {
+        let mut buffer = StreamReadBuffer::new(&self.nl_payload);
+        buffer.set_size_hint(self.nl_payload.len());
+        Ok(Genlmsghdr::deserialize(&mut buffer)?)
+    }
+
+    /// Serialize the message with its header, padded to 4 bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = (NLMSG_HDRLEN + self.nl_payload.len()) as u32;
+        let mut bytes = Vec::with_capacity(len as usize + 3);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&self.nl_type.to_le_bytes());
+        bytes.extend_from_slice(&self.nl_flags.to_le_bytes());
+        bytes.extend_from_slice(&self.nl_seq.to_le_bytes());
+        bytes.extend_from_slice(&self.nl_pid.to_le_bytes());
+        bytes.extend_from_slice(&self.nl_payload);
+        bytes.resize((bytes.len() + 3) & !3, 0);
+        bytes
+    }
+
+    /// Split a datagram into the netlink messages it holds
+    ///
+    /// Parsing stops at the first truncated message.
+    pub fn split(datagram: &[u8]) -> Vec<NlMessage> {
+        let mut messages = Vec::new();
+        let mut rest = datagram;
+
+        while rest.len() >= NLMSG_HDRLEN {
+            let len = u32::from_le_bytes(rest[0..4].try_into().unwrap()) as usize;
+            if len < NLMSG_HDRLEN || len > rest.len() {
+                break;
+            }
+
+            messages.push(NlMessage {
+                nl_type: u16::from_le_bytes(rest[4..6].try_into().unwrap()),
+                nl_flags: u16::from_le_bytes(rest[6..8].try_into().unwrap()),
+                nl_seq: u32::from_le_bytes(rest[8..12].try_into().unwrap()),
+                nl_pid: u32::from_le_bytes(rest[12..16].try_into().unwrap()),
+                nl_payload: rest[NLMSG_HDRLEN..len].to_vec(),
+            });
+
+            rest = rest.get((len + 3) & !3..).unwrap_or(&[]);
+        }
+
+        messages
+    }
+}
+
+#[cfg(test)]
+mod test_message {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let mut first = NlMessage::new(28, 2, vec![5, 1, 0, 0, 1]);
+        first.nl_seq = 7;
+        let second = NlMessage::done();
+
+        let mut datagram = first.to_bytes();
+        assert_eq!(datagram.len(), 24);
+        datagram.extend(second.to_bytes());
+        // Truncated trailing message
+        datagram.extend_from_slice(&[200, 0, 0, 0, 3, 0]);
+
+        assert_eq!(NlMessage::split(&datagram), vec![first, second]);
+    }
+}
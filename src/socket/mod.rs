@@ -2,20 +2,49 @@ use crate::bss::Bss;
 use crate::station::Station;
 use crate::nl80211traits::ParseNlAttr;
 // use crate::station::parse_station;
-use neli::consts::{NlFamily,NlmF,Nlmsg};
+use neli::consts::{NlFamily,NlmF};
 use neli::socket::NlSocket;
-use neli::nl::Nlmsghdr;
-use neli::genl::Genlmsghdr;
-use crate::consts::{NL_80211_GENL_NAME, NL_80211_GENL_VERSION};
+use crate::consts::NL_80211_GENL_NAME;
 use crate::attr::Nl80211Attr;
 use crate::cmd::Nl80211Cmd;
 use crate::interface::Interface;
+use crate::error::Nl80211Error;
+use crate::event::{MulticastGroup, Nl80211Event};
+use crate::pcap::PcapWriter;
+use crate::scan::{ScanRequest, SchedScanRequest};
+use crate::types::MacAddr;
+use crate::wiphy::Wiphy;
+use neli::err::NlError;
 use neli::nlattr::Nlattr;
+use std::fs::File;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+mod message;
+pub use message::*;
+mod transport;
+pub use transport::*;
+mod netns;
+pub use netns::Netns;
+mod session;
+use session::{Reply, Session};
+pub use session::DEFAULT_DUMP_RETRIES;
+#[cfg(feature = "async")]
+mod async_socket;
+#[cfg(feature = "async")]
+pub use async_socket::*;
 
 /// A generic netlink socket to send commands and receive messages
-pub struct Socket {
-    pub sock: NlSocket,
+///
+/// The socket talks to the kernel through a [`Transport`](trait.Transport.html), a netlink socket by
+/// default. Use [`MockTransport`](struct.MockTransport.html) to run it against scripted messages.
+pub struct Socket<T = NetlinkTransport> {
+    transport: T,
     pub family_id: u16,
+    session: Session,
 }
 
 impl Socket {
@@ -24,117 +53,279 @@ impl Socket {
     /// # Example
     ///
     /// ```no_run
-    /// # use nl80211::{Socket, Nl80211Attr, Nl80211Cmd, NL_80211_GENL_VERSION};
-    /// # use neli::nlattr::Nlattr;
-    /// # use neli::genl::Genlmsghdr;
-    /// # use neli::nl::Nlmsghdr;
-    /// # use neli::err::NlError;
-    /// # use neli::consts::{NlFamily,NlmF,Nlmsg};
+    /// # use nl80211::Socket;
     ///
-    /// # fn main() -> Result<(), neli::err::NlError> {
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
     ///     // Create a new nl80211 socket and use this socket to send nl80211 commands
     ///     let mut nl80211sock = Socket::connect()?;
     ///
-    ///     let attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>> = vec![];
-    ///
-    ///     // Create a netlink message header with the command CMD_GET_INTERFACE and some payloads
-    ///     let genlhdr = Genlmsghdr::new(Nl80211Cmd::CmdGetInterface, NL_80211_GENL_VERSION, attrs)?;
-    ///
-    ///     // Crate a netlink header
-    ///     let nlhdr = {
-    ///         let len = None;
-    ///         let nl_type = nl80211sock.family_id;
-    ///         let flags = vec![NlmF::Request, NlmF::Dump]; // Dump info
-    ///         let seq = None;
-    ///         let pid = None;
-    ///         let payload = genlhdr;
-    ///         Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-    ///     };
-    ///
-    ///     // Send netlink message
-    ///     nl80211sock.sock.send_nl(nlhdr)?;
-    ///
-    ///     let mut iter = nl80211sock.sock.iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>();
-    ///     while let Some(Ok(response)) = iter.next() {
-    ///         match response.nl_type {
-    ///            Nlmsg::Error => panic!("Error"),
-    ///            Nlmsg::Done => break,
-    ///            _ => {
-    ///             // Parsing netlink messages here
-    ///            }
-    ///         }
+    ///     for interface in nl80211sock.get_interfaces_info()? {
+    ///         println!("{}", interface);
     ///     }
-    ///
     /// #   Ok(())
     /// # }
     /// ```
-    pub fn connect() -> Result<Self, neli::err::NlError> {
+    pub fn connect() -> Result<Self, Nl80211Error> {
         let family_id = {
             NlSocket::new(NlFamily::Generic, true)?
                 .resolve_genl_family(NL_80211_GENL_NAME)?
         };
 
-        let track_seq = true;
-        let mut nl80211sock = NlSocket::new(NlFamily::Generic, track_seq)?;
-
-        let pid = None;
-        let groups = None;
-        nl80211sock.bind(pid, groups)?;
+        let transport = NetlinkTransport::connect()?;
 
-        Ok(Self {
-            sock: nl80211sock,
-            family_id: family_id,
-        })
+        Ok(Socket::with_transport(transport, family_id))
     }
 
-    /// Get information for all your wifi interfaces
+    /// Create a new nl80211 socket inside a network namespace
+    ///
+    /// `path` is a namespace file, such as /var/run/netns/\<name\> or /proc/\<pid\>/ns/net. Joining
+    /// a namespace requires CAP_SYS_ADMIN.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use nl80211::Socket;
     ///
-    /// # fn main() -> Result<(), neli::err::NlError>{
-    ///     let wifi_interfaces = Socket::connect()?.get_interfaces_info();
-    ///     for wifi_interface in wifi_interfaces? {
-    ///         println!("{}", wifi_interface);
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     let mut socket = Socket::connect_in_netns("/var/run/netns/container")?;
+    ///
+    ///     for interface in socket.get_interfaces_info()? {
+    ///         println!("{}", interface);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn connect_in_netns<P: AsRef<Path>>(path: P) -> Result<Self, Nl80211Error> {
+        let netns = File::open(path)?;
+        Socket::connect_in_netns_fd(netns.as_raw_fd())
+    }
+
+    /// Create a new nl80211 socket inside the network namespace referred to by a file descriptor
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread can't go back to its own namespace once the socket is created.
+    pub fn connect_in_netns_fd(netns: RawFd) -> Result<Self, Nl80211Error> {
+        netns::run_in(netns, Socket::connect)
+    }
+
+    /// Receive the events published by nl80211 on a multicast group
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::{MulticastGroup, Socket};
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     let mut socket = Socket::connect()?;
+    ///     socket.subscribe(MulticastGroup::Scan)?;
+    ///     socket.subscribe(MulticastGroup::Mlme)?;
+    ///
+    ///     for event in socket.events() {
+    ///         println!("{:?}", event?);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe(&mut self, group: MulticastGroup) -> Result<(), Nl80211Error> {
+        let group_id = group.resolve()?;
+        self.add_membership(group_id)
+    }
+
+    /// Scan with an interface and return the BSS found
+    ///
+    /// Joins the scan multicast group, starts the scan and waits for its end. Fails with
+    /// [`Nl80211Error::ScanAborted`](enum.Nl80211Error.html) when the scan is aborted, e.g. by
+    /// [`Socket::abort_scan`](struct.Socket.html#method.abort_scan) from another socket, and with
+    /// [`Nl80211Error::Timeout`](enum.Nl80211Error.html) when it doesn't end within the timeout of
+    /// the request. The kernel answers EBUSY while another scan is running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::{Nl80211ScanFlags, ScanRequest, Socket, Ssid};
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     let request = ScanRequest::new()
+    ///         .ssid(Ssid::default())
+    ///         .flag(Nl80211ScanFlags::ScanFlagFlush);
+    ///
+    ///     for bss in Socket::connect()?.trigger_scan(3, request)? {
+    ///         println!("{}", bss);
     ///     }
     /// #   Ok(())
     /// # }
+    /// ```
+    pub fn trigger_scan(
+        &mut self,
+        ifindex: u32,
+        request: ScanRequest,
+    ) -> Result<Vec<Bss>, Nl80211Error> {
+        self.subscribe(MulticastGroup::Scan)?;
+        self.start_scan(ifindex, &request)?;
+        self.wait_scan(ifindex, request.timeout)
+    }
+}
+
+impl<T: Transport> Socket<T> {
+    /// Create a nl80211 socket on top of a transport
+    ///
+    /// `family_id` is the generic netlink family id of nl80211, used as type of the messages sent.
+    pub fn with_transport(transport: T, family_id: u16) -> Self {
+        Socket {
+            transport,
+            family_id,
+            session: Session::default(),
+        }
+    }
+
+    /// Transport used by this socket
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Transport used by this socket
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Record every datagram sent and received by this socket to a pcap capture
+    ///
+    /// Captures can be opened with Wireshark and replayed with [`PcapPlayer`](struct.PcapPlayer.html).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::Socket;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error>{
+    ///     let mut socket = Socket::connect()?;
+    ///     socket.record_to_file("nl80211.pcap")?;
+    ///     socket.get_interfaces_info()?;
+    /// #   Ok(())
+    /// # }
     ///```
-    pub fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, neli::err::NlError> {
-        let mut interfaces = Vec::new();
-        let nl80211sock = &mut self.sock;
-
-        let attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>> = vec![];
-        let genlhdr = Genlmsghdr::new(Nl80211Cmd::CmdGetInterface, NL_80211_GENL_VERSION, attrs)?;
-
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = vec![NlmF::Request, NlmF::Dump];
-            let seq = None;
-            let pid = None;
-            let payload = genlhdr;
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
+    pub fn record<W: Write + Send + 'static>(&mut self, writer: W) -> Result<(), Nl80211Error> {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        self.session.record(Some(PcapWriter::new(writer)?));
+        Ok(())
+    }
+
+    /// Record every datagram sent and received by this socket to a pcap file
+    pub fn record_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Nl80211Error> {
+        self.record(File::create(path)?)
+    }
 
-        nl80211sock.send_nl(nlhdr)?;
+    /// Stop recording
+    pub fn stop_recording(&mut self) {
+        self.session.record(None);
+    }
+
+    /// Join a multicast group by id
+    ///
+    /// Use [`Socket::subscribe`](struct.Socket.html#method.subscribe) to join a group by name.
+    pub fn add_membership(&mut self, group_id: u32) -> Result<(), Nl80211Error> {
+        self.transport.add_membership(group_id)?;
+        Ok(())
+    }
+
+    /// Block until the next multicast event
+    ///
+    /// Events received while waiting for replies are queued and returned first.
+    pub fn recv_event(&mut self) -> Result<Nl80211Event, Nl80211Error> {
+        let message = self.recv_event_message()?;
+        Nl80211Event::from_genlmsghdr(&message.genlmsghdr()?)
+    }
+
+    /// Block until the next multicast message
+    fn recv_event_message(&mut self) -> Result<NlMessage, Nl80211Error> {
+        loop {
+            if let Some(message) = self.session.next_event() {
+                return Ok(message);
+            }
+
+            let datagram = self.transport.recv()?;
+            self.session.push_datagram(&datagram)?;
+        }
+    }
+
+    /// Block until the next multicast event, `cmd` times out when the deadline is reached
+    fn recv_event_before(
+        &mut self,
+        cmd: Nl80211Cmd,
+        deadline: Instant,
+    ) -> Result<Nl80211Event, Nl80211Error> {
+        loop {
+            if let Some(message) = self.session.next_event() {
+                return Nl80211Event::from_genlmsghdr(&message.genlmsghdr()?);
+            }
 
-        let mut iter = nl80211sock.iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>();
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return Err(Nl80211Error::Timeout { cmd });
+            }
+            self.transport.set_read_timeout(Some(remaining))?;
 
-        while let Some(Ok(response)) = iter.next() {
-            match response.nl_type {
-                Nlmsg::Error => panic!("Error"),
-                Nlmsg::Done => break,
-                _ => {
-                    let handle = response.nl_payload.get_attr_handle();
-                    interfaces.push(Interface::default().parse(handle));
-                },
+            let datagram = match self.transport.recv() {
+                Ok(datagram) => datagram,
+                Err(err) if is_timeout(&err) => return Err(Nl80211Error::Timeout { cmd }),
+                Err(err) => return Err(err.into()),
             };
+            self.session.push_datagram(&datagram)?;
         }
+    }
+
+    /// Iterate over the multicast events, blocking while waiting for them
+    ///
+    /// Events which can't be decoded are returned as errors and skipped, the iterator stops after
+    /// the first error of the socket.
+    pub fn events(&mut self) -> Events<'_, T> {
+        Events {
+            socket: self,
+            failed: false,
+        }
+    }
+
+    /// Get information for all your wifi interfaces
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::Socket;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error>{
+    ///     let wifi_interfaces = Socket::connect()?.get_interfaces_info();
+    ///     for wifi_interface in wifi_interfaces? {
+    ///         println!("{}", wifi_interface);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    ///```
+    pub fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, Nl80211Error> {
+        self.dump_all(Nl80211Cmd::CmdGetInterface, vec![])
+    }
 
-        Ok(interfaces)
+    /// Get every wiphy with its bands and capabilities
+    ///
+    /// The wiphy dump is requested split, as most drivers don't fit it in a single message, and
+    /// the messages of each wiphy are merged back.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::Socket;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error>{
+    ///     for wiphy in Socket::connect()?.get_wiphys()? {
+    ///         println!("{}", wiphy);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    ///```
+    pub fn get_wiphys(&mut self) -> Result<Vec<Wiphy>, Nl80211Error> {
+        let split = Nlattr::new(None, Nl80211Attr::AttrSplitWiphyDump, Vec::<u8>::new())?;
+        let parts = self.dump_all(Nl80211Cmd::CmdGetWiphy, vec![split])?;
+        Ok(Wiphy::merge_split_dump(parts))
     }
 
     /// Get access point information for a specific interface
@@ -144,92 +335,830 @@ impl Socket {
     /// ```no_run
     /// # use nl80211::Socket;
     ///
-    /// # fn main() -> Result<(), neli::err::NlError>{
+    /// # fn main() -> Result<(), nl80211::Nl80211Error>{
     ///   // First of all we need to get wifi interface information to get more data
     ///   let wifi_interfaces = Socket::connect()?.get_interfaces_info();
     ///   for wifi_interface in wifi_interfaces? {
-    ///     if let Some(netlink_index) = wifi_interface.index {
+    ///     // Then for each wifi interface we can fetch station information
+    ///     let station_info = Socket::connect()?.get_station_info(wifi_interface.ifindex)?;
+    ///     println!("{}", station_info);
+    ///   }
+    /// #   Ok(())
+    /// # }
+    ///```
+    ///
+    /// Only the first station is returned, use [`Socket::get_stations`](struct.Socket.html#method.get_stations)
+    /// to get all of them.
+    pub fn get_station_info(&mut self, ifindex: u32) -> Result<Station, Nl80211Error> {
+        let stations = self.get_stations(ifindex)?;
+        Ok(stations.into_iter().next().unwrap_or_default())
+    }
+
+    /// Get the first BSS found by the last scan of an interface
+    ///
+    /// Use [`Socket::get_scan_results`](struct.Socket.html#method.get_scan_results) to get all of them.
+    pub fn get_bss_info(&mut self, ifindex: u32) -> Result<Bss, Nl80211Error> {
+        let bss = self.get_scan_results(ifindex)?;
+        Ok(bss.into_iter().next().unwrap_or_default())
+    }
+
+    /// Get every station known by an interface
+    ///
+    /// A managed interface has a single station, its access point. An access point interface has a
+    /// station for each associated client.
+    ///
+    /// # Example
     ///
-    ///       // Then for each wifi interface we can fetch station information
-    ///       let station_info = Socket::connect()?.get_station_info(&netlink_index.clone())?;
-    ///           println!("{}", station_info);
-    ///       }
+    /// ```no_run
+    /// # use nl80211::Socket;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error>{
+    ///     let mut socket = Socket::connect()?;
+    ///     for station in socket.get_stations(3)? {
+    ///         println!("{}", station);
     ///     }
     /// #   Ok(())
     /// # }
     ///```
-    pub fn get_station_info(&mut self, interface_attr_if_index: &Vec<u8>) -> Result<Station, neli::err::NlError>  {
-        let nl80211sock = &mut self.sock;
-
-        let mut attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>> = vec![];
-        let new_attr= Nlattr::new(None, Nl80211Attr::AttrIfindex, interface_attr_if_index.to_owned())?;
-        attrs.push(new_attr);
-
-        let genlhdr = Genlmsghdr::new(Nl80211Cmd::CmdGetStation, NL_80211_GENL_VERSION, attrs)?;
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = vec![NlmF::Request, NlmF::Dump];
-            let seq = None;
-            let pid = None;
-            let payload = genlhdr;
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
+    pub fn get_stations(&mut self, ifindex: u32) -> Result<Vec<Station>, Nl80211Error> {
+        self.dump_all(Nl80211Cmd::CmdGetStation, vec![ifindex_attr(ifindex)?])
+    }
+
+    /// Get a station by MAC address
+    ///
+    /// The kernel answers ENOENT when the interface doesn't know the station.
+    pub fn get_station(&mut self, ifindex: u32, mac: MacAddr) -> Result<Station, Nl80211Error> {
+        let attrs = vec![ifindex_attr(ifindex)?, mac_attr(mac)?];
+
+        self.request(Nl80211Cmd::CmdGetStation, attrs)?
+            .next()
+            .unwrap_or_else(|| Err(NlError::new("No station returned by the kernel").into()))
+    }
+
+    /// Get every BSS found by the scans of an interface
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::Socket;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error>{
+    ///     let mut socket = Socket::connect()?;
+    ///     for bss in socket.get_scan_results(3)? {
+    ///         println!("{}", bss);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    ///```
+    pub fn get_scan_results(&mut self, ifindex: u32) -> Result<Vec<Bss>, Nl80211Error> {
+        self.dump_all(Nl80211Cmd::CmdGetScan, vec![ifindex_attr(ifindex)?])
+    }
+
+    /// Start a scan without waiting for its end
+    ///
+    /// Join the scan multicast group beforehand to be notified of the end of the scan, see
+    /// [`Socket::wait_scan`](struct.Socket.html#method.wait_scan).
+    pub fn start_scan(&mut self, ifindex: u32, request: &ScanRequest) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdTriggerScan, request.attrs(ifindex)?)?
+            .collect()
+    }
+
+    /// Abort the scan running on an interface
+    ///
+    /// Sockets waiting for the end of the scan get
+    /// [`Nl80211Error::ScanAborted`](enum.Nl80211Error.html). The kernel answers ENOENT when no
+    /// scan is running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::Socket;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     Socket::connect()?.abort_scan(3)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn abort_scan(&mut self, ifindex: u32) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdAbortScan, vec![ifindex_attr(ifindex)?])?
+            .collect()
+    }
+
+    /// Start a scheduled scan, run by the device until it is stopped
+    ///
+    /// Results are notified on the scan multicast group with
+    /// [`Nl80211Event::SchedScanResults`](enum.Nl80211Event.html) events, and fetched with
+    /// [`Socket::get_scan_results`](struct.Socket.html#method.get_scan_results). The kernel
+    /// answers EOPNOTSUPP when the device can't scan on its own, and EINPROGRESS when a scheduled
+    /// scan is already running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::{MatchSet, MulticastGroup, Nl80211Event, ScanPlan, ScanRequest};
+    /// # use nl80211::{SchedScanRequest, Socket};
+    /// # use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     let request = SchedScanRequest::new(ScanRequest::new())
+    ///         .match_set(MatchSet::ssid("home"))
+    ///         .plan(ScanPlan::new(Duration::from_secs(60)));
+    ///
+    ///     let mut socket = Socket::connect()?;
+    ///     socket.subscribe(MulticastGroup::Scan)?;
+    ///     socket.start_sched_scan(3, &request)?;
+    ///
+    ///     for event in socket.events() {
+    ///         if let Nl80211Event::SchedScanResults { .. } = event? {
+    ///             break;
+    ///         }
+    ///     }
+    ///
+    ///     socket.stop_sched_scan(3)?;
+    ///     for bss in socket.get_scan_results(3)? {
+    ///         println!("{}", bss);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn start_sched_scan(
+        &mut self,
+        ifindex: u32,
+        request: &SchedScanRequest,
+    ) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdStartSchedScan, request.attrs(ifindex)?)?
+            .collect()
+    }
 
-        nl80211sock.send_nl(nlhdr)?;
+    /// Stop the scheduled scan of an interface
+    ///
+    /// The kernel answers ENOENT when no scheduled scan is running.
+    pub fn stop_sched_scan(&mut self, ifindex: u32) -> Result<(), Nl80211Error> {
+        self.request::<()>(Nl80211Cmd::CmdStopSchedScan, vec![ifindex_attr(ifindex)?])?
+            .collect()
+    }
 
-        let mut iter = nl80211sock.iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>();
+    /// Wait for the end of the scan of an interface, then get the BSS found
+    ///
+    /// Events of other interfaces and commands received meanwhile are dropped.
+    pub fn wait_scan(&mut self, ifindex: u32, timeout: Duration) -> Result<Vec<Bss>, Nl80211Error> {
+        let deadline = Instant::now() + timeout;
+        let outcome = self.wait_scan_event(ifindex, deadline);
+        self.transport.set_read_timeout(None)?;
+        outcome?;
 
-        while let Some(Ok(response)) = iter.next() {
-            match response.nl_type {
-                    Nlmsg::Error => panic!("Error"),
-                    Nlmsg::Done => break,
-                    _ => {
-                        let  handle = response.nl_payload.get_attr_handle();
-                        return Ok(Station::default().parse(handle));
-                    },
+        self.get_scan_results(ifindex)
+    }
+
+    fn wait_scan_event(&mut self, ifindex: u32, deadline: Instant) -> Result<(), Nl80211Error> {
+        loop {
+            let event = match self.recv_event_before(Nl80211Cmd::CmdTriggerScan, deadline) {
+                Err(Nl80211Error::InvalidAttribute(_)) => continue,
+                event => event?,
             };
+
+            if let Some(outcome) = scan_outcome(&event, ifindex) {
+                return outcome;
+            }
+        }
+    }
+
+    /// Move a wiphy and its interfaces to another network namespace
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::{Netns, Socket};
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     // Move phy0 to the namespace of process 4242
+    ///     Socket::connect()?.set_wiphy_netns(0, Netns::Pid(4242))?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn set_wiphy_netns(&mut self, wiphy: u32, netns: Netns) -> Result<(), Nl80211Error> {
+        let attrs = netns::wiphy_netns_attrs(wiphy, netns)?;
+        self.request::<()>(Nl80211Cmd::CmdSetWiphyNetns, attrs)?.collect()
+    }
+
+    /// Send a command and ask the kernel to acknowledge it
+    ///
+    /// Returns the messages sent back before the ACK, parsed as `P`. Use `()` as `P` for commands
+    /// which only return an ACK. Errors sent by the kernel are returned by the iterator.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::{Nl80211Attr, Nl80211Cmd, Socket, Station};
+    /// # use neli::nlattr::Nlattr;
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     let attrs = vec![
+    ///         Nlattr::new(None, Nl80211Attr::AttrIfindex, 3u32.to_le_bytes().to_vec())?,
+    ///         Nlattr::new(None, Nl80211Attr::AttrMac, vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55])?,
+    ///     ];
+    ///
+    ///     let mut socket = Socket::connect()?;
+    ///     for station in socket.request::<Station>(Nl80211Cmd::CmdGetStation, attrs)? {
+    ///         println!("{}", station?);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    ///```
+    pub fn request<P: ParseNlAttr + Default>(
+        &mut self,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Replies<'_, T, P>, Nl80211Error> {
+        self.send(cmd, vec![NlmF::Request, NlmF::Ack], attrs)?;
+        Ok(Replies::new(self))
+    }
+
+    /// Send a dump command
+    ///
+    /// Messages are received and parsed as `P` while iterating, until the end of the dump. When the
+    /// dumped data changes on the kernel side the iterator returns
+    /// [`Nl80211Error::DumpInterrupted`](enum.Nl80211Error.html), use
+    /// [`Socket::dump_all`](struct.Socket.html#method.dump_all) to restart the dump instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use nl80211::{Interface, Nl80211Cmd, Socket};
+    ///
+    /// # fn main() -> Result<(), nl80211::Nl80211Error> {
+    ///     let mut socket = Socket::connect()?;
+    ///     for interface in socket.dump::<Interface>(Nl80211Cmd::CmdGetInterface, vec![])? {
+    ///         println!("{}", interface?);
+    ///     }
+    /// #   Ok(())
+    /// # }
+    ///```
+    pub fn dump<P: ParseNlAttr + Default>(
+        &mut self,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Replies<'_, T, P>, Nl80211Error> {
+        self.send(cmd, vec![NlmF::Request, NlmF::Dump], attrs)?;
+        Ok(Replies::new(self))
+    }
+
+    /// Send a dump command and collect every message
+    ///
+    /// Interrupted dumps are restarted up to [`dump_retries`](struct.Socket.html#method.set_dump_retries)
+    /// times, then [`Nl80211Error::DumpInterrupted`](enum.Nl80211Error.html) is returned. Messages
+    /// which can't be decoded are skipped.
+    pub fn dump_all<P: ParseNlAttr + Default>(
+        &mut self,
+        cmd: Nl80211Cmd,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Vec<P>, Nl80211Error> {
+        let mut result = self.dump(cmd.clone(), attrs)?.decoded();
+        let mut attempts = 1;
+
+        while let Err(Nl80211Error::DumpInterrupted { .. }) = result {
+            if attempts > self.session.dump_retries {
+                return Err(Nl80211Error::DumpInterrupted { cmd, attempts });
+            }
+
+            let datagram = self.session.encode_retry()?;
+            self.transport.send(&datagram)?;
+            result = Replies::new(self).decoded();
+            attempts += 1;
+        }
+
+        result
+    }
+
+    /// Set how many times an interrupted dump is restarted by the built-in functions, 3 by default
+    pub fn set_dump_retries(&mut self, retries: u32) {
+        self.session.dump_retries = retries;
+    }
+
+    /// Send a new request through the transport
+    fn send(
+        &mut self,
+        cmd: Nl80211Cmd,
+        flags: Vec<NlmF>,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<(), Nl80211Error> {
+        let datagram = self.session.encode_request(self.family_id, cmd, flags, attrs)?;
+        self.transport.send(&datagram)?;
+        Ok(())
+    }
+
+    /// Receive the next message answering the last request
+    fn recv(&mut self) -> Result<NlMessage, Nl80211Error> {
+        loop {
+            if let Some(message) = self.session.next_reply() {
+                return Ok(message);
+            }
+
+            let datagram = self.transport.recv()?;
+            self.session.push_datagram(&datagram)?;
+        }
+    }
+}
+
+/// Error returned by a transport whose read timeout expired
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+/// Outcome of the scan of an interface notified by an event, `None` for unrelated events
+pub(crate) fn scan_outcome(event: &Nl80211Event, ifindex: u32) -> Option<Result<(), Nl80211Error>> {
+    match *event {
+        Nl80211Event::ScanDone {
+            ifindex: Some(index),
+            ..
+        } if index == ifindex => Some(Ok(())),
+        Nl80211Event::ScanAborted {
+            ifindex: Some(index),
+            ..
+        } if index == ifindex => Some(Err(Nl80211Error::ScanAborted { ifindex })),
+        _ => None,
+    }
+}
+
+/// Interface index attribute of a request
+pub(crate) fn ifindex_attr(ifindex: u32) -> Result<Nlattr<Nl80211Attr, Vec<u8>>, Nl80211Error> {
+    Ok(Nlattr::new(None, Nl80211Attr::AttrIfindex, ifindex.to_le_bytes().to_vec())?)
+}
+
+/// Attribute naming a station or a BSS by MAC address
+pub(crate) fn mac_attr(mac: MacAddr) -> Result<Nlattr<Nl80211Attr, Vec<u8>>, Nl80211Error> {
+    Ok(Nlattr::new(None, Nl80211Attr::AttrMac, mac.as_bytes().to_vec())?)
+}
+
+/// Iterator over the replies to a request, parsed as `P`
+///
+/// Created by [`Socket::request`](struct.Socket.html#method.request) and
+/// [`Socket::dump`](struct.Socket.html#method.dump). Ends with the ACK or the end of the dump, or
+/// after the first error. Replies whose attributes can't be decoded are returned as errors without
+/// ending the iterator, so that they can be skipped. Replies left when the iterator is dropped are
+/// discarded by the next request.
+pub struct Replies<'a, T, P> {
+    socket: &'a mut Socket<T>,
+    finished: bool,
+    parsed: PhantomData<P>,
+}
+
+impl<'a, T: Transport, P> Replies<'a, T, P> {
+    fn new(socket: &'a mut Socket<T>) -> Self {
+        Replies {
+            socket,
+            finished: false,
+            parsed: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: Transport, P: ParseNlAttr + Default> Replies<'a, T, P> {
+    /// Collect the replies, skipping the ones whose attributes can't be decoded
+    fn decoded(mut self) -> Result<Vec<P>, Nl80211Error> {
+        let mut replies = Vec::new();
+        while let Some(reply) = self.next() {
+            match reply {
+                Ok(reply) => replies.push(reply),
+                // Only the errors ending the iterator fail the whole request
+                Err(err) if self.finished => return Err(err),
+                Err(_) => (),
+            }
+        }
+        Ok(replies)
+    }
+}
+
+impl<'a, T: Transport, P: ParseNlAttr + Default> Iterator for Replies<'a, T, P> {
+    type Item = Result<P, Nl80211Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let reply = self
+            .socket
+            .recv()
+            .and_then(|message| self.socket.session.decode_reply(&message));
+
+        match reply {
+            Ok(Reply::Data(genlhdr)) => Some(P::default().parse(genlhdr.get_attr_handle())),
+            Ok(Reply::Ack) | Ok(Reply::Done) => {
+                self.finished = true;
+                None
+            }
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
         }
-        return Ok(Station::default())
     }
+}
 
-    pub fn get_bss_info(&mut self, interface_attr_if_index: &Vec<u8>) -> Result<Bss, neli::err::NlError> {
-        let nl80211sock = &mut self.sock;
+/// Iterator over the multicast events received by a socket
+///
+/// Created by [`Socket::events`](struct.Socket.html#method.events).
+pub struct Events<'a, T> {
+    socket: &'a mut Socket<T>,
+    failed: bool,
+}
 
-        let mut attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>> = vec![];
+impl<'a, T: Transport> Iterator for Events<'a, T> {
+    type Item = Result<Nl80211Event, Nl80211Error>;
 
-        let new_attr= Nlattr::new(None, Nl80211Attr::AttrIfindex, interface_attr_if_index.to_owned())?;
-        attrs.push(new_attr);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
 
-        let genlhdr = Genlmsghdr::new(Nl80211Cmd::CmdGetScan, NL_80211_GENL_VERSION, attrs)?;
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = vec![NlmF::Request, NlmF::Dump];
-            let seq = None;
-            let pid = None;
-            let payload = genlhdr;
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
+        let message = match self.socket.recv_event_message() {
+            Ok(message) => message,
+            Err(err) => {
+                self.failed = true;
+                return Some(Err(err));
+            }
         };
+        Some(message.genlmsghdr().and_then(|genlhdr| Nl80211Event::from_genlmsghdr(&genlhdr)))
+    }
+}
+
+#[cfg(test)]
+mod test_socket {
+    use super::*;
+    use crate::error::AttrError;
 
-        nl80211sock.send_nl(nlhdr)?;
+    #[test]
+    fn test_get_interfaces_info() {
+        let mut transport = MockTransport::new();
+        for ifindex in &[3, 4] {
+            let attrs = vec![ifindex_attr(*ifindex).unwrap()];
+            transport.push_reply(NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs).unwrap());
+        }
+        transport.push_reply(NlMessage::done());
 
-        let mut iter = nl80211sock.iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>();
+        let mut socket = Socket::with_transport(transport, 28);
+        let interfaces = socket.get_interfaces_info().unwrap();
 
-        while let Some(Ok(response)) = iter.next() {
-            match response.nl_type {
-                    Nlmsg::Error => panic!("Error"),
-                    Nlmsg::Done => break,
-                    _ => {
-                        let  handle = response.nl_payload.get_attr_handle();
-                        return Ok(Bss::default().parse(handle))
-                    }
-                }
+        let indexes: Vec<_> = interfaces.into_iter().map(|i| i.ifindex).collect();
+        assert_eq!(indexes, vec![3, 4]);
+
+        let sent = socket.transport().sent_messages();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].nl_type, 28);
+        assert_eq!(sent[0].nl_payload[0], u8::from(Nl80211Cmd::CmdGetInterface));
+    }
+
+    #[test]
+    fn test_request() {
+        let mut transport = MockTransport::new();
+        let attrs = vec![ifindex_attr(3).unwrap()];
+        transport.push_reply(NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs).unwrap());
+        transport.push_reply(NlMessage::error(0));
+        transport.push_reply(NlMessage::error(0));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        let attrs = vec![ifindex_attr(3).unwrap()];
+        let interfaces: Vec<Interface> = socket
+            .request(Nl80211Cmd::CmdGetInterface, attrs)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(interfaces.len(), 1);
+
+        // Commands answered by an ACK alone
+        let replies: Vec<()> = socket
+            .request(Nl80211Cmd::CmdSetInterface, vec![ifindex_attr(3).unwrap()])
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(replies.is_empty());
+
+        let sent = socket.transport().sent_messages();
+        assert_eq!(sent[0].nl_flags, u16::from(NlmF::Request) | u16::from(NlmF::Ack));
+    }
+
+    fn interface_message(wiphy: u32, ifindex: u32, generation: u32) -> NlMessage {
+        let attrs = vec![
+            Nlattr::new(None, Nl80211Attr::AttrWiphy, wiphy.to_le_bytes().to_vec()).unwrap(),
+            ifindex_attr(ifindex).unwrap(),
+            Nlattr::new(None, Nl80211Attr::AttrGeneration, generation.to_le_bytes().to_vec()).unwrap(),
+        ];
+        NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs).unwrap()
+    }
+
+    #[test]
+    fn test_interrupted_dump() {
+        let mut transport = MockTransport::new();
+        let mut push = |mut message: NlMessage, seq: u32| {
+            message.nl_seq = seq;
+            transport.push_message(message);
+        };
+        // First attempt, the generation changes in the middle of the dump
+        push(interface_message(0, 3, 1), 1);
+        push(interface_message(0, 4, 2), 1);
+        push(NlMessage::done(), 1);
+        // Second attempt, the kernel flags the dump as interrupted
+        let mut interrupted = interface_message(0, 3, 2);
+        interrupted.nl_flags |= NLM_F_DUMP_INTR;
+        push(interrupted, 2);
+        push(NlMessage::done(), 2);
+        // Third attempt is consistent
+        push(interface_message(0, 3, 2), 3);
+        push(interface_message(0, 4, 2), 3);
+        push(NlMessage::done(), 3);
+
+        let mut socket = Socket::with_transport(transport.clone(), 28);
+        assert_eq!(socket.get_interfaces_info().unwrap().len(), 2);
+
+        let sent = socket.transport().sent_messages();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0].nl_payload, sent[2].nl_payload);
+        assert!(sent[2].nl_seq > sent[0].nl_seq);
+
+        let mut socket = Socket::with_transport(transport, 28);
+        socket.set_dump_retries(1);
+        match socket.get_interfaces_info() {
+            Err(Nl80211Error::DumpInterrupted { cmd, attempts }) => {
+                assert_eq!(cmd, Nl80211Cmd::CmdGetInterface);
+                assert_eq!(attempts, 2);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dump_several_wiphys() {
+        let mut transport = MockTransport::new();
+        // Each wiphy has its own generation
+        transport.push_reply(interface_message(0, 3, 7));
+        transport.push_reply(interface_message(0, 4, 7));
+        transport.push_reply(interface_message(1, 5, 2));
+        transport.push_reply(NlMessage::done());
+
+        let mut socket = Socket::with_transport(transport, 28);
+        assert_eq!(socket.get_interfaces_info().unwrap().len(), 3);
+        assert_eq!(socket.transport().sent_messages().len(), 1);
+    }
+
+    #[test]
+    fn test_get_stations() {
+        let mut transport = MockTransport::new();
+        for mac in &[[1, 2, 3, 4, 5, 6], [1, 2, 3, 4, 5, 7]] {
+            let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrMac, mac.to_vec()).unwrap()];
+            transport.push_reply(NlMessage::genl(28, Nl80211Cmd::CmdNewStation, attrs).unwrap());
+        }
+        transport.push_reply(NlMessage::done());
+        transport.push_reply(NlMessage::error(libc::ENOENT));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        let stations = socket.get_stations(3).unwrap();
+        assert_eq!(stations.len(), 2);
+        assert_eq!(stations[1].mac, MacAddr([1, 2, 3, 4, 5, 7]));
+
+        match socket.get_station(3, MacAddr([1, 2, 3, 4, 5, 8])) {
+            Err(Nl80211Error::Kernel(error)) => assert_eq!(error.errno, libc::ENOENT),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        let sent = socket.transport().sent_messages();
+        assert_eq!(sent[1].nl_flags, u16::from(NlmF::Request) | u16::from(NlmF::Ack));
+    }
+
+    #[test]
+    fn test_get_wiphys() {
+        let mut transport = MockTransport::new();
+        for (index, max_scan_ssids) in &[(0u32, Some(4)), (0, None), (1, Some(20))] {
+            let wiphy = index.to_le_bytes().to_vec();
+            let mut attrs = vec![Nlattr::new(None, Nl80211Attr::AttrWiphy, wiphy).unwrap()];
+            if let Some(max_scan_ssids) = max_scan_ssids {
+                let ssids = vec![*max_scan_ssids];
+                attrs.push(Nlattr::new(None, Nl80211Attr::AttrMaxNumScanSsids, ssids).unwrap());
+            }
+            transport.push_reply(NlMessage::genl(28, Nl80211Cmd::CmdNewWiphy, attrs).unwrap());
+        }
+        transport.push_reply(NlMessage::done());
+
+        let mut socket = Socket::with_transport(transport, 28);
+        let wiphys = socket.get_wiphys().unwrap();
+        assert_eq!(wiphys.len(), 2);
+        assert_eq!(wiphys[0].max_scan_ssids, Some(4));
+        assert_eq!(wiphys[1].index, 1);
+        assert_eq!(wiphys[1].max_scan_ssids, Some(20));
+    }
+
+    #[test]
+    fn test_skip_invalid_replies() {
+        let mut transport = MockTransport::new();
+        for _ in 0..2 {
+            for payload in [vec![3, 0, 0, 0], vec![4, 0], vec![5, 0, 0, 0]] {
+                let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrIfindex, payload).unwrap()];
+                let message = NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs).unwrap();
+                transport.push_reply(message);
             }
-        Ok(Bss::default())
+            transport.push_reply(NlMessage::done());
+        }
+
+        let mut socket = Socket::with_transport(transport, 28);
+        let replies: Vec<_> = socket
+            .dump::<Interface>(Nl80211Cmd::CmdGetInterface, vec![])
+            .unwrap()
+            .collect();
+        assert_eq!(replies.len(), 3);
+
+        match &replies[1] {
+            Err(Nl80211Error::InvalidAttribute(error)) => assert_eq!(
+                error,
+                &AttrError {
+                    attr: "AttrIfindex".to_string(),
+                    expected: 4,
+                    actual: 2,
+                }
+            ),
+            other => panic!("unexpected {:?}", other.as_ref().map(|_| ())),
+        }
+
+        let indexes: Vec<_> = replies.into_iter().flatten().map(|i| i.ifindex).collect();
+        assert_eq!(indexes, vec![3, 5]);
+
+        // The built-in functions skip them
+        let interfaces = socket.get_interfaces_info().unwrap();
+        assert_eq!(interfaces.iter().map(|i| i.ifindex).collect::<Vec<_>>(), vec![3, 5]);
+    }
+
+    #[test]
+    fn test_abort_scan() {
+        let mut transport = MockTransport::new();
+        transport.push_reply(NlMessage::error(0));
+        transport.push_reply(NlMessage::error(0));
+        // The kernel notifies the abort to the waiter
+        transport.push_message(scan_event(Nl80211Cmd::CmdScanAborted, 3));
+        transport.push_reply(NlMessage::error(libc::ENOENT));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        socket.start_scan(3, &ScanRequest::new()).unwrap();
+        socket.abort_scan(3).unwrap();
+        match socket.wait_scan(3, Duration::from_secs(1)) {
+            Err(Nl80211Error::ScanAborted { ifindex }) => assert_eq!(ifindex, 3),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        match socket.abort_scan(3) {
+            Err(Nl80211Error::Kernel(error)) => assert_eq!(error.errno, libc::ENOENT),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let sent = socket.transport().sent_messages();
+        let abort = sent[1].genlmsghdr().unwrap();
+        assert_eq!(abort.cmd, Nl80211Cmd::CmdAbortScan);
+        assert_eq!(
+            abort.get_attr_handle().iter().next().map(|attr| attr.payload.clone()),
+            Some(vec![3, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_sched_scan() {
+        let mut transport = MockTransport::new();
+        transport.push_reply(NlMessage::error(0));
+        transport.push_message(scan_event(Nl80211Cmd::CmdSchedScanResults, 3));
+        transport.push_reply(NlMessage::error(0));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        let request = SchedScanRequest::new(ScanRequest::new())
+            .plan(crate::scan::ScanPlan::new(Duration::from_secs(30)));
+        socket.start_sched_scan(3, &request).unwrap();
+        assert_eq!(
+            socket.recv_event().unwrap(),
+            Nl80211Event::SchedScanResults { wiphy: None, ifindex: Some(3) }
+        );
+        socket.stop_sched_scan(3).unwrap();
+
+        let cmds: Vec<_> = socket
+            .transport()
+            .sent_messages()
+            .iter()
+            .map(|message| message.genlmsghdr().unwrap().cmd)
+            .collect();
+        assert_eq!(cmds, vec![Nl80211Cmd::CmdStartSchedScan, Nl80211Cmd::CmdStopSchedScan]);
+    }
+
+    fn scan_event(cmd: Nl80211Cmd, ifindex: u32) -> NlMessage {
+        NlMessage::genl(28, cmd, vec![ifindex_attr(ifindex).unwrap()]).unwrap()
     }
 
-    // pub fn scan(&mut self) -> Result<(), neli::err::NlError> {
-    //     Ok(())
-    // }
+    #[test]
+    fn test_scan() {
+        let mut transport = MockTransport::new();
+        transport.push_reply(NlMessage::error(0));
+        transport.push_message(scan_event(Nl80211Cmd::CmdTriggerScan, 3));
+        transport.push_message(scan_event(Nl80211Cmd::CmdNewScanResults, 4));
+        transport.push_message(scan_event(Nl80211Cmd::CmdNewScanResults, 3));
+        let mut bss = Vec::new();
+        crate::scan::put_attr(&mut bss, 1, &[1, 2, 3, 4, 5, 6]);
+        let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrBss, bss).unwrap()];
+        transport.push_reply(NlMessage::genl(28, Nl80211Cmd::CmdNewScanResults, attrs).unwrap());
+        transport.push_reply(NlMessage::done());
+
+        let mut socket = Socket::with_transport(transport, 28);
+        let request = ScanRequest::new().ssid("hidden");
+        socket.start_scan(3, &request).unwrap();
+        let results = socket.wait_scan(3, Duration::from_secs(1)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].bssid, Some(MacAddr([1, 2, 3, 4, 5, 6])));
+
+        let sent = socket.transport().sent_messages();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].genlmsghdr().unwrap().cmd, Nl80211Cmd::CmdTriggerScan);
+        assert_eq!(sent[1].genlmsghdr().unwrap().cmd, Nl80211Cmd::CmdGetScan);
+    }
+
+    #[test]
+    fn test_scan_failures() {
+        let mut transport = MockTransport::new();
+        transport.push_reply(NlMessage::error(0));
+        transport.push_message(scan_event(Nl80211Cmd::CmdScanAborted, 3));
+        transport.push_reply(NlMessage::error(0));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        socket.start_scan(3, &ScanRequest::new()).unwrap();
+        match socket.wait_scan(3, Duration::from_secs(1)) {
+            Err(Nl80211Error::ScanAborted { ifindex }) => assert_eq!(ifindex, 3),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        // Nothing else is received before the deadline
+        socket.start_scan(3, &ScanRequest::new()).unwrap();
+        match socket.wait_scan(3, Duration::from_secs(1)) {
+            Err(Nl80211Error::Timeout { cmd }) => assert_eq!(cmd, Nl80211Cmd::CmdTriggerScan),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_set_wiphy_netns() {
+        let mut transport = MockTransport::new();
+        transport.push_reply(NlMessage::error(0));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        socket.set_wiphy_netns(1, Netns::Pid(4242)).unwrap();
+
+        let sent = socket.transport().sent_messages();
+        let genlhdr = sent[0].genlmsghdr().unwrap();
+        assert_eq!(genlhdr.cmd, Nl80211Cmd::CmdSetWiphyNetns);
+
+        let attrs: Vec<_> = genlhdr
+            .get_attr_handle()
+            .iter()
+            .map(|attr| (attr.nla_type.clone(), attr.payload.clone()))
+            .collect();
+        assert_eq!(
+            attrs,
+            vec![
+                (Nl80211Attr::AttrWiphy, vec![1, 0, 0, 0]),
+                (Nl80211Attr::AttrPid, vec![146, 16, 0, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let mut transport = MockTransport::new();
+        let attrs = vec![ifindex_attr(3).unwrap()];
+        transport.push_message(NlMessage::genl(28, Nl80211Cmd::CmdNewScanResults, attrs).unwrap());
+        // A multicast event received in the middle of a dump
+        transport.push_reply(NlMessage::done());
+        let attrs = vec![ifindex_attr(3).unwrap()];
+        transport.push_message(NlMessage::genl(28, Nl80211Cmd::CmdRoam, attrs).unwrap());
+
+        let mut socket = Socket::with_transport(transport, 28);
+        socket.add_membership(5).unwrap();
+        assert!(socket.get_interfaces_info().unwrap().is_empty());
+
+        let events: Vec<_> = socket.events().collect();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0].as_ref().unwrap(),
+            &Nl80211Event::ScanDone { wiphy: None, ifindex: Some(3) }
+        );
+        assert_eq!(
+            events[1].as_ref().unwrap(),
+            &Nl80211Event::Roam { ifindex: Some(3), bssid: None }
+        );
+        // The mock transport ends the stream with an error
+        assert!(events[2].is_err());
+        assert_eq!(socket.transport().memberships(), &[5]);
+    }
+
+    #[test]
+    fn test_kernel_error() {
+        let mut transport = MockTransport::new();
+        transport.push_reply(NlMessage::error(libc::EPERM));
+
+        let mut socket = Socket::with_transport(transport, 28);
+        match socket.get_station_info(3) {
+            Err(Nl80211Error::PermissionDenied(_)) => (),
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+    }
 }
@@ -0,0 +1,71 @@
+use crate::attr::Nl80211Attr;
+use crate::error::Nl80211Error;
+use neli::nlattr::Nlattr;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// Network namespace a wiphy is moved to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Netns {
+    /// Namespace of a process
+    Pid(u32),
+    /// Namespace file descriptor, such as an opened /var/run/netns/\<name\> or /proc/\<pid\>/ns/net
+    Fd(RawFd),
+}
+
+/// Attributes of the CmdSetWiphyNetns command
+pub(crate) fn wiphy_netns_attrs(
+    wiphy: u32,
+    netns: Netns,
+) -> Result<Vec<Nlattr<Nl80211Attr, Vec<u8>>>, Nl80211Error> {
+    let target = match netns {
+        Netns::Pid(pid) => Nlattr::new(None, Nl80211Attr::AttrPid, pid.to_le_bytes().to_vec())?,
+        Netns::Fd(fd) => {
+            let fd = (fd as u32).to_le_bytes().to_vec();
+            Nlattr::new(None, Nl80211Attr::AttrNetnsFd, fd)?
+        }
+    };
+
+    Ok(vec![
+        Nlattr::new(None, Nl80211Attr::AttrWiphy, wiphy.to_le_bytes().to_vec())?,
+        target,
+    ])
+}
+
+/// Run `f` with the current thread moved to another network namespace
+///
+/// The thread always goes back to its namespace afterwards, even when `f` panics. Sockets opened
+/// by `f` stay in the namespace they were created in.
+///
+/// # Panics
+///
+/// Panics if the thread can't go back to its namespace, like iproute2 it can't carry on in the
+/// wrong one.
+pub(crate) fn run_in<F, R>(netns: RawFd, f: F) -> Result<R, Nl80211Error>
+where
+    F: FnOnce() -> Result<R, Nl80211Error>,
+{
+    let _restore = Restore(File::open("/proc/thread-self/ns/net")?);
+
+    setns(netns)?;
+    f()
+}
+
+/// Moves the current thread back to a network namespace when dropped
+struct Restore(File);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Err(err) = setns(self.0.as_raw_fd()) {
+            panic!("Can't restore the network namespace of the thread: {}", err);
+        }
+    }
+}
+
+fn setns(fd: RawFd) -> io::Result<()> {
+    if unsafe { libc::setns(fd, libc::CLONE_NEWNET) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+use crate::consts::NL_80211_GENL_VERSION;
+use crate::error::Nl80211Error;
+use crate::parse_attr::parse_u32;
+use crate::pcap::{Direction, PcapWriter};
+use crate::socket::message::NlMessage;
+use buffering::StreamWriteBuffer;
+use neli::consts::{NlmF, Nlmsg};
+use neli::genl::Genlmsghdr;
+use neli::nl::Nlmsghdr;
+use neli::nlattr::Nlattr;
+use neli::Nl;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+
+/// Number of times an interrupted dump is restarted by default
+pub const DEFAULT_DUMP_RETRIES: u32 = 3;
+
+/// Transport independent state of a nl80211 socket
+///
+/// Numbers requests, matches replies with the last request, queues multicast events and records
+/// the traffic. Shared by the blocking and the async sockets, which only differ in the way
+/// datagrams are moved.
+pub(crate) struct Session {
+    seq: u32,
+    /// Last request sent, kept to restart interrupted dumps
+    request: Vec<u8>,
+    cmd: Option<Nl80211Cmd>,
+    /// Generation of the data dumped so far (AttrGeneration), by wiphy
+    ///
+    /// The kernel numbers generations per wiphy, replies without AttrWiphy describe a single
+    /// interface and are keyed by `None`.
+    generations: HashMap<Option<u32>, u32>,
+    received: VecDeque<NlMessage>,
+    events: VecDeque<NlMessage>,
+    recorder: Option<PcapWriter<Box<dyn Write + Send>>>,
+    /// Number of times an interrupted dump is restarted before giving up
+    pub dump_retries: u32,
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session {
+            seq: 0,
+            request: Vec::new(),
+            cmd: None,
+            generations: HashMap::new(),
+            received: VecDeque::new(),
+            events: VecDeque::new(),
+            recorder: None,
+            dump_retries: DEFAULT_DUMP_RETRIES,
+        }
+    }
+}
+
+/// Meaning of a reply for the request it answers
+pub(crate) enum Reply {
+    /// A message carrying nl80211 attributes
+    Data(Genlmsghdr<Nl80211Cmd, Nl80211Attr>),
+    /// The request was acknowledged
+    Ack,
+    /// End of a dump
+    Done,
+}
+
+impl Session {
+    /// Number a new request and serialize it
+    pub fn encode_request(
+        &mut self,
+        family_id: u16,
+        cmd: Nl80211Cmd,
+        flags: Vec<NlmF>,
+        attrs: Vec<Nlattr<Nl80211Attr, Vec<u8>>>,
+    ) -> Result<Vec<u8>, Nl80211Error> {
+        self.next_seq();
+        self.cmd = Some(cmd.clone());
+
+        let genlhdr = Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs)?;
+        let nlhdr = {
+            let len = None;
+            let nl_type = family_id;
+            let seq = Some(self.seq);
+            let pid = None;
+            let payload = genlhdr;
+            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
+        };
+
+        let mut buffer = StreamWriteBuffer::new_growable(Some(nlhdr.asize()));
+        nlhdr.serialize(&mut buffer)?;
+        self.request = buffer.as_ref().to_vec();
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.write_datagram(Direction::Sent, &self.request)?;
+        }
+
+        Ok(self.request.clone())
+    }
+
+    /// Renumber the last request to send it again
+    pub fn encode_retry(&mut self) -> Result<Vec<u8>, Nl80211Error> {
+        self.next_seq();
+        if let Some(seq) = self.request.get_mut(8..12) {
+            seq.copy_from_slice(&self.seq.to_le_bytes());
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.write_datagram(Direction::Sent, &self.request)?;
+        }
+
+        Ok(self.request.clone())
+    }
+
+    fn next_seq(&mut self) {
+        // Sequence number 0 is left to multicast events
+        self.seq = self.seq.wrapping_add(1).max(1);
+        self.generations.clear();
+    }
+
+    /// Decode a reply to the last request
+    ///
+    /// Replies flagged with NLM_F_DUMP_INTR, or whose AttrGeneration differs from the previous
+    /// ones of the same wiphy, make an interrupted dump.
+    pub fn decode_reply(&mut self, message: &NlMessage) -> Result<Reply, Nl80211Error> {
+        let reply = Reply::decode(message)?;
+
+        let mut interrupted = message.is_dump_interrupted();
+        if let Reply::Data(genlhdr) = &reply {
+            let handle = genlhdr.get_attr_handle();
+            let find_u32 = |nla_type: Nl80211Attr| {
+                handle
+                    .iter()
+                    .find(|attr| attr.nla_type == nla_type)
+                    // A malformed value can't tell whether the dump changed, it is ignored
+                    .and_then(|attr| parse_u32(attr).ok())
+            };
+
+            if let Some(generation) = find_u32(Nl80211Attr::AttrGeneration) {
+                let wiphy = find_u32(Nl80211Attr::AttrWiphy);
+                let previous = self.generations.insert(wiphy, generation);
+                interrupted |= previous.is_some_and(|previous| previous != generation);
+            }
+        }
+
+        if interrupted {
+            return Err(Nl80211Error::DumpInterrupted {
+                cmd: self.cmd.clone().unwrap_or(Nl80211Cmd::CmdUnspec),
+                attempts: 1,
+            });
+        }
+
+        Ok(reply)
+    }
+
+    /// Queue the messages of a received datagram
+    pub fn push_datagram(&mut self, datagram: &[u8]) -> Result<(), Nl80211Error> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.write_datagram(Direction::Received, datagram)?;
+        }
+        for message in NlMessage::split(datagram) {
+            if message.nl_seq == 0 && !is_control(&message) {
+                self.events.push_back(message);
+            } else {
+                self.received.push_back(message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Next queued message answering the last request
+    ///
+    /// Messages left over from previous requests are dropped.
+    pub fn next_reply(&mut self) -> Option<NlMessage> {
+        while let Some(message) = self.received.pop_front() {
+            if message.nl_seq == self.seq {
+                return Some(message);
+            }
+        }
+        None
+    }
+
+    /// Next queued multicast event
+    pub fn next_event(&mut self) -> Option<NlMessage> {
+        self.events.pop_front()
+    }
+
+    pub fn record(&mut self, recorder: Option<PcapWriter<Box<dyn Write + Send>>>) {
+        self.recorder = recorder;
+    }
+}
+
+/// Error, ACK and end of dump messages
+fn is_control(message: &NlMessage) -> bool {
+    matches!(Nlmsg::from(message.nl_type), Nlmsg::Error | Nlmsg::Done)
+}
+
+impl Reply {
+    /// Decode a reply, errors sent by the kernel are turned into `Err`
+    pub fn decode(message: &NlMessage) -> Result<Reply, Nl80211Error> {
+        match Nlmsg::from(message.nl_type) {
+            Nlmsg::Error => match Nl80211Error::from_nlmsgerr(&message.nl_payload) {
+                Some(err) => Err(err),
+                None => Ok(Reply::Ack),
+            },
+            Nlmsg::Done => Ok(Reply::Done),
+            _ => Ok(Reply::Data(message.genlmsghdr()?)),
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use crate::socket::message::NlMessage;
+use neli::consts::NlFamily;
+use neli::socket::NlSocket;
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::Duration;
+
+/// Size of the buffer used to receive a netlink datagram
+const RECV_BUFFER_SIZE: usize = 32768;
+
+/// Moves raw netlink datagrams between a nl80211 socket and the kernel
+///
+/// A datagram may hold several netlink messages, framing is done by the socket.
+pub trait Transport {
+    /// Send a datagram
+    fn send(&mut self, buf: &[u8]) -> io::Result<()>;
+
+    /// Block until the next datagram is received
+    fn recv(&mut self) -> io::Result<Vec<u8>>;
+
+    /// Make `recv` fail with `WouldBlock` or `TimedOut` when nothing is received in time, `None`
+    /// blocks forever
+    ///
+    /// Transports which can't time out ignore it.
+    fn set_read_timeout(&mut self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Join a netlink multicast group
+    ///
+    /// Transports without multicast support ignore subscriptions.
+    fn add_membership(&mut self, _group: u32) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Transport backed by a generic netlink socket
+pub struct NetlinkTransport {
+    sock: NlSocket,
+}
+
+impl NetlinkTransport {
+    /// Open and bind a generic netlink socket
+    pub fn connect() -> io::Result<NetlinkTransport> {
+        let track_seq = false;
+        let mut sock = NlSocket::new(NlFamily::Generic, track_seq)?;
+
+        let pid = None;
+        let groups = None;
+        sock.bind(pid, groups)?;
+
+        // Kernels older than 4.12 reject the option with ENOPROTOOPT, errors are then reported without
+        // message and offending attribute
+        match enable_ext_ack(&sock) {
+            Err(ref e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => (),
+            res => res?,
+        }
+
+        Ok(NetlinkTransport { sock })
+    }
+
+    /// Move the socket in or out of nonblocking mode
+    ///
+    /// In nonblocking mode `send` and `recv` fail with `WouldBlock` instead of waiting.
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        let fd = self.sock.as_raw_fd();
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let flags = if nonblocking {
+            flags | libc::O_NONBLOCK
+        } else {
+            flags & !libc::O_NONBLOCK
+        };
+        if unsafe { libc::fcntl(fd, libc::F_SETFL, flags) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Underlying netlink socket
+    pub fn socket(&self) -> &NlSocket {
+        &self.sock
+    }
+
+    /// Underlying netlink socket
+    pub fn socket_mut(&mut self) -> &mut NlSocket {
+        &mut self.sock
+    }
+}
+
+impl Transport for NetlinkTransport {
+    fn send(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.sock.send(buf, 0)?;
+        Ok(())
+    }
+
+    fn recv(&mut self) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; RECV_BUFFER_SIZE];
+        let len = self.sock.recv(&mut buf[..], 0)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        // A zero timeval blocks forever, shorter timeouts are rounded up to a microsecond
+        let timeval = match timeout {
+            Some(timeout) if timeout.as_secs() == 0 => libc::timeval {
+                tv_sec: 0,
+                tv_usec: timeout.subsec_micros().max(1) as libc::suseconds_t,
+            },
+            Some(timeout) => libc::timeval {
+                tv_sec: timeout.as_secs() as libc::time_t,
+                tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+            },
+            None => libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+        };
+        let ret = unsafe {
+            libc::setsockopt(
+                self.sock.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeval as *const libc::timeval as *const libc::c_void,
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn add_membership(&mut self, group: u32) -> io::Result<()> {
+        let ret = unsafe {
+            libc::setsockopt(
+                self.sock.as_raw_fd(),
+                libc::SOL_NETLINK,
+                libc::NETLINK_ADD_MEMBERSHIP,
+                &group as *const u32 as *const libc::c_void,
+                std::mem::size_of::<u32>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl AsRawFd for NetlinkTransport {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sock.as_raw_fd()
+    }
+}
+
+/// Ask the kernel to attach a message and the offset of the offending attribute to error messages
+fn enable_ext_ack(sock: &NlSocket) -> io::Result<()> {
+    let enable: libc::c_int = 1;
+    let ret = unsafe {
+        libc::setsockopt(
+            sock.as_raw_fd(),
+            libc::SOL_NETLINK,
+            libc::NETLINK_EXT_ACK,
+            &enable as *const libc::c_int as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// In-memory transport replaying scripted messages
+///
+/// Replies get the sequence number of the last request sent, so they are accepted by the socket
+/// whatever the number of requests made before. Once every message is consumed `recv` fails, with
+/// `WouldBlock` when a read timeout is set.
+///
+/// # Example
+///
+/// ```
+/// # use nl80211::{MockTransport, NlMessage, Socket, Nl80211Attr, Nl80211Cmd};
+/// # use neli::nlattr::Nlattr;
+/// # fn main() -> Result<(), nl80211::Nl80211Error> {
+/// let mut transport = MockTransport::new();
+/// let attrs = vec![Nlattr::new(None, Nl80211Attr::AttrIfindex, vec![3, 0, 0, 0])?];
+/// transport.push_reply(NlMessage::genl(28, Nl80211Cmd::CmdNewInterface, attrs)?);
+/// transport.push_reply(NlMessage::done());
+///
+/// let interfaces = Socket::with_transport(transport, 28).get_interfaces_info()?;
+/// assert_eq!(interfaces[0].ifindex, 3);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockTransport {
+    replies: VecDeque<(NlMessage, bool)>,
+    sent: Vec<Vec<u8>>,
+    memberships: Vec<u32>,
+    last_seq: u32,
+    read_timeout: Option<Duration>,
+}
+
+impl MockTransport {
+    pub fn new() -> MockTransport {
+        MockTransport::default()
+    }
+
+    /// Queue a reply to the next request
+    pub fn push_reply(&mut self, message: NlMessage) {
+        self.replies.push_back((message, true));
+    }
+
+    /// Queue a message which is not a reply, its sequence number is kept as is
+    pub fn push_message(&mut self, message: NlMessage) {
+        self.replies.push_back((message, false));
+    }
+
+    /// Datagrams sent through this transport
+    pub fn sent(&self) -> &[Vec<u8>] {
+        &self.sent
+    }
+
+    /// Multicast groups joined through this transport
+    pub fn memberships(&self) -> &[u32] {
+        &self.memberships
+    }
+
+    /// Messages sent through this transport
+    pub fn sent_messages(&self) -> Vec<NlMessage> {
+        self.sent
+            .iter()
+            .flat_map(|datagram| NlMessage::split(datagram))
+            .collect()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&mut self, buf: &[u8]) -> io::Result<()> {
+        if let Some(seq) = buf.get(8..12) {
+            self.last_seq = u32::from_le_bytes(seq.try_into().unwrap());
+        }
+        self.sent.push(buf.to_vec());
+        Ok(())
+    }
+
+    fn recv(&mut self) -> io::Result<Vec<u8>> {
+        match self.replies.pop_front() {
+            Some((mut message, is_reply)) => {
+                if is_reply {
+                    message.nl_seq = self.last_seq;
+                }
+                Ok(message.to_bytes())
+            }
+            None if self.read_timeout.is_some() => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "no more scripted messages",
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "no more scripted messages",
+            )),
+        }
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.read_timeout = timeout;
+        Ok(())
+    }
+
+    fn add_membership(&mut self, group: u32) -> io::Result<()> {
+        self.memberships.push(group);
+        Ok(())
+    }
+}
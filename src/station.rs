@@ -1,175 +1,354 @@
 use std::fmt;
-use crate::attr::{Nl80211Attr, Nl80211StaInfo};
+use std::convert::TryInto;
+use std::time::Duration;
+use crate::attr::{
+    NlaNested, Nl80211Attr, Nl80211RateInfo, Nl80211StaBssParam, Nl80211StaFlags, Nl80211StaInfo,
+};
+use crate::error::{AttrError, Nl80211Error};
 use crate::nl80211traits::*;
-use crate::parse_attr::{parse_hex, parse_i8, parse_u32, parse_u8};
+use crate::parse_attr::{fixed_payload, parse_i8, parse_u16, parse_u32, parse_u64, parse_u8};
+use crate::rate_info::RateInfo;
+use crate::tid_stats::TidStats;
+use crate::types::{Dbm, MacAddr, Tu};
 use neli::nlattr::AttrHandle;
 
 /// A struct representing a remote station (Access Point)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Station {
-    /// Signal strength average (i8, dBm)
-    pub average_signal: Option<Vec<u8>>,
-    /// Count of times beacon loss was detected (u32)
-    pub beacon_loss: Option<Vec<u8>>,
-    /// Station bssid (u8)
-    pub bssid: Option<Vec<u8>>,
-    /// Time since the station is last connected in seconds (u32)
-    pub connected_time: Option<Vec<u8>>,
-    /// Reception bitrate (u8)
-    pub rx_bitrate: Option<Vec<u8>>,
-    /// Total received packets (MSDUs and MMPDUs) from this station (u32)
-    pub rx_packets: Option<Vec<u8>>,
-    /// Signal strength of last received PPDU (u8, dBm)
-    pub signal: Option<Vec<u8>>,
-    /// Transmission bitrate (u8)
-    pub tx_bitrate: Option<Vec<u8>>,
-    /// Total failed packets (MPDUs) to this station (u32)
-    pub tx_failed: Option<Vec<u8>>,
-    /// Total transmitted packets (MSDUs and MMPDUs) to this station (u32)
-    pub tx_packets: Option<Vec<u8>>,
-    /// Total retries (MPDUs) to this station (u32)
-    pub tx_retries: Option<Vec<u8>>,
+    /// Station MAC address, the bssid of the access point for a managed interface
+    pub mac: MacAddr,
+    /// Signal strength of last received PPDU
+    pub signal: Dbm,
+    /// Signal strength average
+    pub signal_avg: Dbm,
+    /// Time since the station is last connected
+    pub connected: Duration,
+    /// Time since last activity
+    pub inactive: Duration,
+    /// Count of times beacon loss was detected
+    pub beacon_loss: u32,
+    /// Total received bytes (MPDU length) from this station
+    pub rx_bytes: u64,
+    /// Total transmitted bytes (MPDU length) to this station
+    pub tx_bytes: u64,
+    /// Total received packets (MSDUs and MMPDUs) from this station
+    pub rx_packets: u64,
+    /// Total transmitted packets (MSDUs and MMPDUs) to this station
+    pub tx_packets: u64,
+    /// Total retries (MPDUs) to this station
+    pub tx_retries: u64,
+    /// Total failed packets (MPDUs) to this station
+    pub tx_failed: u64,
+    /// Bitrate of the last frame received from this station
+    pub rx_bitrate: Option<RateInfo>,
+    /// Bitrate of the last frame sent to this station
+    pub tx_bitrate: Option<RateInfo>,
+    /// Expected throughput in kbit/s, 802.11 headers included
+    pub expected_throughput: Option<u32>,
+    /// Signal strength of last received PPDU, per antenna
+    pub chain_signal: Vec<Dbm>,
+    /// Signal strength average, per antenna
+    pub chain_signal_avg: Vec<Dbm>,
+    /// Signal strength of the last ACK frame received from this station
+    pub ack_signal: Option<Dbm>,
+    /// Signal strength average of the ACK frames received from this station
+    pub ack_signal_avg: Option<Dbm>,
+    /// Received packets dropped for unspecified reasons
+    pub rx_drop_misc: u64,
+    /// Beacons received from this station
+    pub beacon_rx: u64,
+    /// Signal strength average of the beacons received from this station
+    pub beacon_signal_avg: Option<Dbm>,
+    /// Time spent transmitting to this station
+    pub tx_duration: Duration,
+    /// Time spent receiving from this station
+    pub rx_duration: Duration,
+    /// Station flags
+    pub sta_flags: Option<StaFlags>,
+    /// Parameters of the BSS the station belongs to
+    pub bss_param: Option<BssParam>,
+    /// Traffic statistics per TID
+    pub tid_stats: Vec<TidStats>,
 }
 
-impl Station {
-    pub fn default() -> Station {
-        Station {
-            bssid: None,
-            connected_time: None,
-            beacon_loss: None,
-            signal: None,
-            average_signal: None,
-            rx_packets: None,
-            tx_packets: None,
-            rx_bitrate: None,
-            tx_bitrate: None,
-            tx_retries: None,
-            tx_failed: None,
+/// Station flags (struct nl80211_sta_flag_update)
+///
+/// Bits are indexed by `Nl80211StaFlags`, only the flags in `mask` are reported by the kernel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StaFlags {
+    pub mask: u32,
+    pub set: u32,
+}
+
+impl StaFlags {
+    /// Whether a flag is set, `None` when the kernel didn't report it
+    pub fn get(&self, flag: Nl80211StaFlags) -> Option<bool> {
+        let bit = 1u32.checked_shl(u16::from(flag) as u32)?;
+        if self.mask & bit == 0 {
+            return None;
+        }
+        Some(self.set & bit != 0)
+    }
+
+    /// Flags reported as set
+    pub fn flags(&self) -> Vec<Nl80211StaFlags> {
+        (1..=u16::from(Nl80211StaFlags::StaFlagMax))
+            .map(Nl80211StaFlags::from)
+            .filter(|flag| self.get(flag.clone()) == Some(true))
+            .collect()
+    }
+}
+
+/// Parameters of the BSS a station belongs to (StaInfoBssParam)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BssParam {
+    /// CTS protection is used
+    pub cts_protection: bool,
+    /// Short preamble is used
+    pub short_preamble: bool,
+    /// Short slot time is used
+    pub short_slot_time: bool,
+    /// DTIM period, in beacon intervals
+    pub dtim_period: u8,
+    /// Beacon interval
+    pub beacon_interval: Tu,
+}
+
+impl BssParam {
+    fn parse(handle: AttrHandle<Nl80211StaBssParam>) -> Result<BssParam, Nl80211Error> {
+        let mut bss_param = BssParam::default();
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211StaBssParam::StaBssParamCtsProt => bss_param.cts_protection = true,
+                Nl80211StaBssParam::StaBssParamShortPreamble => bss_param.short_preamble = true,
+                Nl80211StaBssParam::StaBssParamShortSlotTime => bss_param.short_slot_time = true,
+                Nl80211StaBssParam::StaBssParamDtimPeriod => {
+                    bss_param.dtim_period = parse_u8(attr)?
+                }
+                Nl80211StaBssParam::StaBssParamBeaconInterval => {
+                    bss_param.beacon_interval = Tu(parse_u16(attr)? as u32)
+                }
+                _ => (),
+            }
         }
+        Ok(bss_param)
     }
 }
 
+/// Per antenna signal strengths nested in StaInfoChainSignal and StaInfoChainSignalAvg
+fn parse_chain_signal(handle: AttrHandle<NlaNested>) -> Result<Vec<Dbm>, AttrError> {
+    handle
+        .iter()
+        .map(|attr| parse_i8(attr).map(|signal| Dbm(signal as i32)))
+        .collect()
+}
+
 impl ParseNlAttr for Station {
     /// Parse netlink messages returned by the nl80211 command CmdGetStation
-    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Station {
+    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Result<Station, Nl80211Error> {
         for attr in handle.iter() {
             match attr.nla_type {
                 Nl80211Attr::AttrMac => {
-                    self.bssid = Some(attr.payload.clone());
+                    self.mac = MacAddr(fixed_payload(attr, 6)?.try_into().unwrap());
                 }
                 Nl80211Attr::AttrStaInfo => {
-                    let sub_handle = attr.get_nested_attributes::<Nl80211StaInfo>().unwrap();
+                    let sub_handle = attr.get_nested_attributes::<Nl80211StaInfo>()?;
+                    // 32 bits byte counters wrap quickly, the 64 bits ones are preferred
+                    let mut rx_bytes64 = None;
+                    let mut tx_bytes64 = None;
+
                     for sub_attr in sub_handle.iter() {
                         match sub_attr.nla_type {
                             Nl80211StaInfo::StaInfoSignal => {
-                                self.signal = Some(sub_attr.payload.clone())
+                                self.signal = Dbm(parse_i8(sub_attr)? as i32)
                             }
                             Nl80211StaInfo::StaInfoSignalAvg => {
-                                self.average_signal = Some(sub_attr.payload.clone())
+                                self.signal_avg = Dbm(parse_i8(sub_attr)? as i32)
                             }
                             Nl80211StaInfo::StaInfoBeaconLoss => {
-                                self.beacon_loss = Some(sub_attr.payload.clone())
+                                self.beacon_loss = parse_u32(sub_attr)?
                             }
                             Nl80211StaInfo::StaInfoConnectedTime => {
-                                self.connected_time = Some(sub_attr.payload.clone())
+                                self.connected = Duration::from_secs(parse_u32(sub_attr)? as u64)
+                            }
+                            Nl80211StaInfo::StaInfoInactiveTime => {
+                                self.inactive = Duration::from_millis(parse_u32(sub_attr)? as u64)
+                            }
+                            Nl80211StaInfo::StaInfoRxBytes => {
+                                self.rx_bytes = parse_u32(sub_attr)? as u64
+                            }
+                            Nl80211StaInfo::StaInfoTxBytes => {
+                                self.tx_bytes = parse_u32(sub_attr)? as u64
+                            }
+                            Nl80211StaInfo::StaInfoRxBytes64 => {
+                                rx_bytes64 = Some(parse_u64(sub_attr)?)
+                            }
+                            Nl80211StaInfo::StaInfoTxBytes64 => {
+                                tx_bytes64 = Some(parse_u64(sub_attr)?)
                             }
                             Nl80211StaInfo::StaInfoRxPackets => {
-                                self.rx_packets = Some(sub_attr.payload.clone())
+                                self.rx_packets = parse_u32(sub_attr)? as u64
                             }
                             Nl80211StaInfo::StaInfoTxPackets => {
-                                self.tx_packets = Some(sub_attr.payload.clone())
+                                self.tx_packets = parse_u32(sub_attr)? as u64
                             }
                             Nl80211StaInfo::StaInfoTxRetries => {
-                                self.tx_retries = Some(sub_attr.payload.clone())
+                                self.tx_retries = parse_u32(sub_attr)? as u64
                             }
                             Nl80211StaInfo::StaInfoTxFailed => {
-                                self.tx_failed = Some(sub_attr.payload.clone())
+                                self.tx_failed = parse_u32(sub_attr)? as u64
                             }
                             Nl80211StaInfo::StaInfoRxBitrate => {
-                                let bit_rate_handle =
-                                    sub_attr.get_nested_attributes::<Nl80211StaInfo>().unwrap();
-                                for sub_sub_attr in bit_rate_handle.iter() {
-                                    match sub_sub_attr.nla_type {
-                                        Nl80211StaInfo::StaInfoRxBytes => {
-                                            self.rx_bitrate = Some(sub_sub_attr.payload.clone())
-                                        }
-                                        _ => (),
-                                    }
-                                }
+                                let rate_handle =
+                                    sub_attr.get_nested_attributes::<Nl80211RateInfo>()?;
+                                self.rx_bitrate = Some(RateInfo::parse(rate_handle)?)
                             }
                             Nl80211StaInfo::StaInfoTxBitrate => {
-                                let bit_rate_handle =
-                                    sub_attr.get_nested_attributes::<Nl80211StaInfo>().unwrap();
-                                for sub_sub_attr in bit_rate_handle.iter() {
-                                    match sub_sub_attr.nla_type {
-                                        Nl80211StaInfo::StaInfoRxBytes => {
-                                            self.tx_bitrate = Some(sub_sub_attr.payload.clone())
-                                        }
-                                        _ => (),
-                                    }
-                                }
+                                let rate_handle =
+                                    sub_attr.get_nested_attributes::<Nl80211RateInfo>()?;
+                                self.tx_bitrate = Some(RateInfo::parse(rate_handle)?)
+                            }
+                            Nl80211StaInfo::StaInfoExpectedThroughput => {
+                                self.expected_throughput = Some(parse_u32(sub_attr)?)
+                            }
+                            Nl80211StaInfo::StaInfoChainSignal => {
+                                let chain_handle =
+                                    sub_attr.get_nested_attributes::<NlaNested>()?;
+                                self.chain_signal = parse_chain_signal(chain_handle)?
+                            }
+                            Nl80211StaInfo::StaInfoChainSignalAvg => {
+                                let chain_handle =
+                                    sub_attr.get_nested_attributes::<NlaNested>()?;
+                                self.chain_signal_avg = parse_chain_signal(chain_handle)?
+                            }
+                            Nl80211StaInfo::StaInfoAckSignal => {
+                                self.ack_signal = Some(Dbm(parse_i8(sub_attr)? as i32))
+                            }
+                            Nl80211StaInfo::StaInfoAckSignalAvg => {
+                                self.ack_signal_avg = Some(Dbm(parse_i8(sub_attr)? as i32))
+                            }
+                            Nl80211StaInfo::StaInfoRxDropMisc => {
+                                self.rx_drop_misc = parse_u64(sub_attr)?
+                            }
+                            Nl80211StaInfo::StaInfoBeaconRx => {
+                                self.beacon_rx = parse_u64(sub_attr)?
+                            }
+                            Nl80211StaInfo::StaInfoBeaconSignalAvg => {
+                                self.beacon_signal_avg = Some(Dbm(parse_i8(sub_attr)? as i32))
+                            }
+                            Nl80211StaInfo::StaInfoTxDuration => {
+                                self.tx_duration = Duration::from_micros(parse_u64(sub_attr)?)
+                            }
+                            Nl80211StaInfo::StaInfoRxDuration => {
+                                self.rx_duration = Duration::from_micros(parse_u64(sub_attr)?)
+                            }
+                            Nl80211StaInfo::StaInfoStaFlags => {
+                                // struct nl80211_sta_flag_update, the mask then the set flags
+                                let flags = parse_u64(sub_attr)?;
+                                self.sta_flags = Some(StaFlags {
+                                    mask: flags as u32,
+                                    set: (flags >> 32) as u32,
+                                })
+                            }
+                            Nl80211StaInfo::StaInfoTidStats => {
+                                let tid_handle =
+                                    sub_attr.get_nested_attributes::<NlaNested>()?;
+                                self.tid_stats = TidStats::parse_all(tid_handle)?
+                            }
+                            Nl80211StaInfo::StaInfoBssParam => {
+                                let bss_handle =
+                                    sub_attr.get_nested_attributes::<Nl80211StaBssParam>()?;
+                                self.bss_param = Some(BssParam::parse(bss_handle)?)
                             }
                             _ => (),
                         }
                     }
+
+                    self.rx_bytes = rx_bytes64.unwrap_or(self.rx_bytes);
+                    self.tx_bytes = tx_bytes64.unwrap_or(self.tx_bytes);
                 }
                 _ => (),
             }
         }
-        self.to_owned()
+        Ok(self.to_owned())
     }
 }
 
+/// Per antenna signal strengths as printed by iw, " [-40, -43]"
+fn format_chain(chain: &[Dbm]) -> String {
+    if chain.is_empty() {
+        return String::new();
+    }
+    let signals: Vec<_> = chain.iter().map(|signal| signal.0.to_string()).collect();
+    format!(" [{}]", signals.join(", "))
+}
+
 impl fmt::Display for Station {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut result = Vec::new();
 
-        if let Some(bssid) = &self.bssid {
-            result.push(format!("bssid : {}", parse_hex(bssid)))
-        };
-
-        if let Some(connected_time) = &self.connected_time {
-            result.push(format!(
-                "connected time : {} minutes",
-                parse_u32(connected_time) as f32 / 60.0
-            ))
-        };
-
-        if let Some(beacon_loss) = &self.beacon_loss {
-            result.push(format!("beacon loss : {}", parse_u32(beacon_loss)))
-        };
+        result.push(format!("mac : {}", self.mac));
+        result.push(format!(
+            "connected time : {} minutes",
+            self.connected.as_secs() as f32 / 60.0
+        ));
+        result.push(format!("inactive time : {} ms", self.inactive.as_millis()));
+        result.push(format!("beacon loss : {}", self.beacon_loss));
+        result.push(format!("signal : {}{}", self.signal, format_chain(&self.chain_signal)));
+        result.push(format!(
+            "average signal : {}{}",
+            self.signal_avg,
+            format_chain(&self.chain_signal_avg)
+        ));
 
-        if let Some(signal) = &self.signal {
-            result.push(format!("signal : {} dBm", parse_i8(signal)))
+        if let Some(ack_signal) = &self.ack_signal {
+            result.push(format!("ack signal : {}", ack_signal))
         };
 
-        if let Some(average_signal) = &self.average_signal {
-            result.push(format!("average signal : {} dBm", parse_i8(average_signal)))
+        if let Some(beacon_signal_avg) = &self.beacon_signal_avg {
+            result.push(format!("beacon signal average : {}", beacon_signal_avg))
         };
 
-        if let Some(rx_packets) = &self.rx_packets {
-            result.push(format!("rx packets : {}", parse_u32(rx_packets)))
+        if let Some(expected_throughput) = &self.expected_throughput {
+            result.push(format!(
+                "expected throughput : {} Mb/s",
+                *expected_throughput as f64 / 1000.0
+            ))
         };
 
-        if let Some(tx_packets) = &self.tx_packets {
-            result.push(format!("tx packets : {}", parse_u32(tx_packets)))
-        };
+        result.push(format!("rx bytes : {}", self.rx_bytes));
+        result.push(format!("tx bytes : {}", self.tx_bytes));
+        result.push(format!("rx packets : {}", self.rx_packets));
+        result.push(format!("tx packets : {}", self.tx_packets));
 
         if let Some(rx_bitrate) = &self.rx_bitrate {
-            result.push(format!("rx bitrate : {} Mb/s", parse_u8(rx_bitrate) * 10))
+            result.push(format!("rx bitrate : {}", rx_bitrate))
         };
 
         if let Some(tx_bitrate) = &self.tx_bitrate {
-            result.push(format!("tx bitrate : {} Mb/s", parse_u8(tx_bitrate) * 10))
+            result.push(format!("tx bitrate : {}", tx_bitrate))
         }
 
-        if let Some(tx_retries) = &self.tx_retries {
-            result.push(format!("tx retries : {}", parse_u32(tx_retries)))
-        }
+        result.push(format!("tx retries : {}", self.tx_retries));
+        result.push(format!("tx failed : {}", self.tx_failed));
+        result.push(format!("rx drop misc : {}", self.rx_drop_misc));
+        result.push(format!("beacon rx : {}", self.beacon_rx));
 
-        if let Some(tx_failed) = &self.tx_failed {
-            result.push(format!("tx failed : {}", parse_u32(tx_failed)))
-        }
+        if let Some(sta_flags) = &self.sta_flags {
+            let flags: Vec<_> = sta_flags.flags().iter().map(|flag| format!("{:?}", flag)).collect();
+            result.push(format!("flags : {}", flags.join(" ")))
+        };
+
+        if let Some(bss_param) = &self.bss_param {
+            result.push(format!(
+                "bss : dtim period {}, beacon interval {}{}{}{}",
+                bss_param.dtim_period,
+                bss_param.beacon_interval,
+                if bss_param.cts_protection { ", CTS protection" } else { "" },
+                if bss_param.short_preamble { ", short preamble" } else { "" },
+                if bss_param.short_slot_time { ", short slot time" } else { "" },
+            ))
+        };
 
         write!(f, "{}", result.join("\n"))
     }
@@ -185,30 +364,52 @@ mod tests_station {
     #[test]
     fn test_pretty_format() {
         let station = Station {
-            average_signal: Some(vec![197]),
-            beacon_loss: Some(vec![0, 0, 0, 0]),
-            bssid: Some(vec![255, 255, 255, 255, 255, 255]),
-            connected_time: Some(vec![118, 21, 0, 0]),
-            rx_bitrate: Some(vec![12]),
-            rx_packets: Some(vec![108, 126, 6, 0]),
-            signal: Some(vec![195]),
-            tx_bitrate: Some(vec![13]),
-            tx_failed: Some(vec![45, 0, 0, 0]),
-            tx_packets: Some(vec![14, 89, 2, 0]),
-            tx_retries: Some(vec![9, 111, 0, 0]),
+            mac: MacAddr([255, 255, 255, 255, 255, 255]),
+            signal: Dbm(-61),
+            signal_avg: Dbm(-59),
+            connected: Duration::from_secs(5494),
+            inactive: Duration::from_millis(760),
+            beacon_loss: 0,
+            rx_bytes: 496788011,
+            tx_bytes: 23952227,
+            rx_packets: 425580,
+            tx_packets: 153870,
+            tx_retries: 28425,
+            tx_failed: 45,
+            rx_bitrate: Some(RateInfo {
+                bitrate: Some(1200),
+                mcs: Some(12),
+                ..RateInfo::default()
+            }),
+            tx_bitrate: Some(RateInfo {
+                bitrate: Some(1300),
+                mcs: Some(13),
+                ..RateInfo::default()
+            }),
+            chain_signal: vec![Dbm(-61), Dbm(-64)],
+            expected_throughput: Some(41156),
+            sta_flags: Some(StaFlags { mask: 0xfe, set: 0xaa }),
+            ..Station::default()
         };
 
-        let expected_output = r#"bssid : FF:FF:FF:FF:FF:FF
+        let expected_output = r#"mac : FF:FF:FF:FF:FF:FF
         connected time : 91.566666 minutes
+        inactive time : 760 ms
         beacon loss : 0
-        signal : -61 dBm
+        signal : -61 dBm [-61, -64]
         average signal : -59 dBm
+        expected throughput : 41.156 Mb/s
+        rx bytes : 496788011
+        tx bytes : 23952227
         rx packets : 425580
         tx packets : 153870
-        rx bitrate : 120 Mb/s
-        tx bitrate : 130 Mb/s
+        rx bitrate : 120.0 MBit/s MCS 12
+        tx bitrate : 130.0 MBit/s MCS 13
         tx retries : 28425
-        tx failed : 45"#;
+        tx failed : 45
+        rx drop misc : 0
+        beacon rx : 0
+        flags : StaFlagAuthorized StaFlagWme StaFlagAuthenticated StaFlagAssociated"#;
 
         assert_eq!(
             format!("{}", station),
@@ -328,21 +529,66 @@ mod tests_station {
             },
         ];
 
-        let station = Station::default().parse(neli::nlattr::AttrHandle::Owned(handler));
+        let station = Station::default()
+            .parse(neli::nlattr::AttrHandle::Owned(handler))
+            .unwrap();
         let expected_station = Station {
-            average_signal: Some(vec![215]),
-            beacon_loss: Some(vec![0, 0, 0, 0]),
-            bssid: Some(vec![46, 46, 46, 46, 46, 46]),
-            connected_time: Some(vec![17, 27, 0, 0]),
-            rx_bitrate: Some(vec![4]),
-            rx_packets: Some(vec![226, 128, 7, 0]),
-            signal: Some(vec![218]),
-            tx_bitrate: Some(vec![13]),
-            tx_failed: Some(vec![47, 0, 0, 0]),
-            tx_packets: Some(vec![9, 170, 2, 0]),
-            tx_retries: Some(vec![27, 130, 0, 0]),
+            mac: MacAddr([46, 46, 46, 46, 46, 46]),
+            signal: Dbm(-38),
+            signal_avg: Dbm(-41),
+            connected: Duration::from_secs(6929),
+            inactive: Duration::from_millis(760),
+            beacon_loss: 0,
+            rx_bytes: 496788011,
+            tx_bytes: 23952227,
+            rx_packets: 491746,
+            tx_packets: 174601,
+            tx_retries: 33307,
+            tx_failed: 47,
+            rx_bitrate: Some(RateInfo {
+                bitrate: Some(390),
+                mcs: Some(4),
+                ..RateInfo::default()
+            }),
+            tx_bitrate: Some(RateInfo {
+                bitrate: Some(1040),
+                mcs: Some(13),
+                ..RateInfo::default()
+            }),
+            expected_throughput: Some(41156),
+            chain_signal: vec![Dbm(-40), Dbm(-43)],
+            chain_signal_avg: vec![Dbm(-44), Dbm(-45)],
+            ack_signal: Some(Dbm(46)),
+            ack_signal_avg: None,
+            rx_drop_misc: 951,
+            beacon_rx: 65249,
+            beacon_signal_avg: Some(Dbm(-40)),
+            tx_duration: Duration::default(),
+            rx_duration: Duration::default(),
+            sta_flags: Some(StaFlags { mask: 0xfe, set: 0xaa }),
+            bss_param: Some(BssParam {
+                cts_protection: false,
+                short_preamble: true,
+                short_slot_time: true,
+                dtim_period: 1,
+                beacon_interval: Tu(100),
+            }),
+            tid_stats: station.tid_stats.clone(),
         };
 
-        assert_eq!(station, expected_station)
+        assert_eq!(station, expected_station);
+
+        assert_eq!(station.tid_stats.len(), 17);
+        assert_eq!(station.tid_stats[0].rx_msdu, 354216);
+        assert_eq!(station.tid_stats[7].tx_msdu, 2);
+        let txq_stats = station.tid_stats[0].txq_stats.as_ref().unwrap();
+        assert_eq!((txq_stats.drops, txq_stats.tx_packets), (23, 174407));
+        assert_eq!(station.tid_stats[16].txq_stats, None);
+
+        let handler = vec![Nlattr::new(None, AttrMac, vec![46; 7]).unwrap()];
+        match Station::default().parse(neli::nlattr::AttrHandle::Owned(handler)) {
+            Err(Nl80211Error::InvalidAttribute(err)) => assert_eq!(err.actual, 7),
+            other => panic!("unexpected {:?}", other),
+        }
     }
 }
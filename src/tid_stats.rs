@@ -0,0 +1,175 @@
+use crate::attr::{NlaNested, Nl80211TidStats, Nl80211TxqStats};
+use crate::error::{AttrError, Nl80211Error};
+use crate::parse_attr::{parse_u32, parse_u64};
+use neli::nlattr::AttrHandle;
+
+/// Traffic statistics of a station for a traffic identifier (TID)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TidStats {
+    /// Traffic identifier, 0 to 15, or 16 for non-QoS traffic
+    pub tid: u8,
+    /// Received MSDUs
+    pub rx_msdu: u64,
+    /// Transmitted MSDUs
+    pub tx_msdu: u64,
+    /// Retries of transmitted MSDUs
+    pub tx_msdu_retries: u64,
+    /// Failed transmitted MSDUs
+    pub tx_msdu_failed: u64,
+    /// Statistics of the TXQ of this TID, for drivers using the mac80211 TXQs
+    pub txq_stats: Option<TxqStats>,
+}
+
+/// Statistics of a transmit queue
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TxqStats {
+    /// Bytes currently queued
+    pub backlog_bytes: u32,
+    /// Packets currently queued
+    pub backlog_packets: u32,
+    /// Number of new flows seen
+    pub flows: u32,
+    /// Packets dropped
+    pub drops: u32,
+    /// Packets marked with ECN
+    pub ecn_marks: u32,
+    /// Packets dropped because the queue limit was reached
+    pub overlimit: u32,
+    /// Packets dropped because the memory limit was reached
+    pub overmemory: u32,
+    /// Hash collisions
+    pub collisions: u32,
+    /// Bytes dequeued
+    pub tx_bytes: u32,
+    /// Packets dequeued
+    pub tx_packets: u32,
+    /// Number of flow buckets
+    pub max_flows: u32,
+}
+
+impl TidStats {
+    /// Decode the per TID entries nested in StaInfoTidStats
+    ///
+    /// Entries are indexed from 1, the last one holds the non-QoS traffic.
+    pub fn parse_all(handle: AttrHandle<NlaNested>) -> Result<Vec<TidStats>, Nl80211Error> {
+        let mut tid_stats = Vec::new();
+
+        for attr in handle.iter() {
+            let index = u16::from(attr.nla_type.clone());
+            let stats_handle = attr.get_nested_attributes::<Nl80211TidStats>()?;
+
+            let mut stats = TidStats {
+                tid: index.saturating_sub(1) as u8,
+                ..TidStats::default()
+            };
+
+            for stat in stats_handle.iter() {
+                match stat.nla_type {
+                    Nl80211TidStats::TidStatsRxMsdu => stats.rx_msdu = parse_u64(stat)?,
+                    Nl80211TidStats::TidStatsTxMsdu => stats.tx_msdu = parse_u64(stat)?,
+                    Nl80211TidStats::TidStatsTxMsduRetries => {
+                        stats.tx_msdu_retries = parse_u64(stat)?
+                    }
+                    Nl80211TidStats::TidStatsTxMsduFailed => {
+                        stats.tx_msdu_failed = parse_u64(stat)?
+                    }
+                    Nl80211TidStats::TidStatsTxqStats => {
+                        let txq_handle = stat.get_nested_attributes::<Nl80211TxqStats>()?;
+                        stats.txq_stats = Some(TxqStats::parse(txq_handle)?);
+                    }
+                    _ => (),
+                }
+            }
+
+            tid_stats.push(stats);
+        }
+
+        Ok(tid_stats)
+    }
+}
+
+impl TxqStats {
+    fn parse(handle: AttrHandle<Nl80211TxqStats>) -> Result<TxqStats, AttrError> {
+        let mut stats = TxqStats::default();
+
+        for attr in handle.iter() {
+            // Attributes added by newer kernels may not be u32, they are skipped undecoded
+            match attr.nla_type {
+                Nl80211TxqStats::TxqStatsBacklogBytes => stats.backlog_bytes = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsBacklogPackets => stats.backlog_packets = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsFlows => stats.flows = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsDrops => stats.drops = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsEcnMarks => stats.ecn_marks = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsOverlimit => stats.overlimit = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsOvermemory => stats.overmemory = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsCollisions => stats.collisions = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsTxBytes => stats.tx_bytes = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsTxPackets => stats.tx_packets = parse_u32(attr)?,
+                Nl80211TxqStats::TxqStatsMaxFlows => stats.max_flows = parse_u32(attr)?,
+                _ => (),
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test_tid_stats {
+    use super::*;
+
+    #[test]
+    fn test_parse_all() {
+        let tid_0 = vec![
+            56, 0, 1, 0, 12, 0, 1, 0, 168, 103, 5, 0, 0, 0, 0, 0, 12, 0, 2, 0, 71, 169, 2, 0, 0,
+            0, 0, 0, 28, 0, 6, 0, 8, 0, 3, 0, 61, 39, 1, 0, 8, 0, 4, 0, 23, 0, 0, 0, 8, 0, 9, 0, 38,
+            56, 109, 1,
+        ];
+        let non_qos = vec![
+            28, 0, 17, 0, 12, 0, 1, 0, 109, 25, 0, 0, 0, 0, 0, 0, 12, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0,
+            0,
+        ];
+        let payload = [tid_0, non_qos].concat();
+        let handle = neli::nlattr::Nlattr::new(None, NlaNested::Unspec, payload).unwrap();
+        let tid_stats =
+            TidStats::parse_all(handle.get_nested_attributes::<NlaNested>().unwrap()).unwrap();
+
+        assert_eq!(
+            tid_stats,
+            vec![
+                TidStats {
+                    tid: 0,
+                    rx_msdu: 354216,
+                    tx_msdu: 174407,
+                    txq_stats: Some(TxqStats {
+                        flows: 75581,
+                        drops: 23,
+                        tx_bytes: 23935014,
+                        ..TxqStats::default()
+                    }),
+                    ..TidStats::default()
+                },
+                TidStats {
+                    tid: 16,
+                    rx_msdu: 6509,
+                    tx_msdu_failed: 1,
+                    ..TidStats::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_txq_stats_unknown_attribute() {
+        let attr = |nla_type, payload| neli::nlattr::Nlattr::new(None, nla_type, payload).unwrap();
+        let handler = vec![
+            attr(Nl80211TxqStats::TxqStatsDrops, vec![23, 0, 0, 0]),
+            attr(Nl80211TxqStats::from(20), vec![1, 0, 0, 0, 0, 0, 0, 0]),
+        ];
+        let stats = TxqStats::parse(AttrHandle::Owned(handler)).unwrap();
+        assert_eq!(stats.drops, 23);
+
+        let handler = vec![attr(Nl80211TxqStats::TxqStatsDrops, vec![23, 0])];
+        assert!(TxqStats::parse(AttrHandle::Owned(handler)).is_err());
+    }
+}
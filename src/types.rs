@@ -0,0 +1,307 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A MAC address
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl MacAddr {
+    /// Broadcast address, FF:FF:FF:FF:FF:FF
+    pub const BROADCAST: MacAddr = MacAddr([0xff; 6]);
+
+    /// Read a MAC address from an attribute payload, `None` unless it is 6 bytes long
+    pub fn from_bytes(bytes: &[u8]) -> Option<MacAddr> {
+        let mut mac = [0; 6];
+        if bytes.len() != mac.len() {
+            return None;
+        }
+        mac.copy_from_slice(bytes);
+        Some(MacAddr(mac))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        *self == MacAddr::BROADCAST
+    }
+
+    /// Group address, the broadcast address included
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    pub fn is_unicast(&self) -> bool {
+        !self.is_multicast()
+    }
+
+    /// Address set by software rather than assigned by the manufacturer, such as a random
+    /// address used to scan
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(bytes: [u8; 6]) -> MacAddr {
+        MacAddr(bytes)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mac = self.0;
+        write!(
+            f,
+            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+            mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]
+        )
+    }
+}
+
+/// Error returned when parsing a MAC address fails
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseMacAddrError(String);
+
+impl fmt::Display for ParseMacAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid MAC address: {:?}", self.0)
+    }
+}
+
+impl Error for ParseMacAddrError {}
+
+impl FromStr for MacAddr {
+    type Err = ParseMacAddrError;
+
+    /// Parse six hexadecimal bytes separated by colons or dashes, e.g. "02:00:5e:10:00:01"
+    fn from_str(s: &str) -> Result<MacAddr, ParseMacAddrError> {
+        let error = || ParseMacAddrError(s.to_string());
+        let mut mac = [0; 6];
+        let mut bytes = s.split([':', '-']);
+
+        for byte in mac.iter_mut() {
+            let hex = bytes.next().ok_or_else(error)?;
+            if hex.len() != 2 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err(error());
+            }
+            *byte = u8::from_str_radix(hex, 16).map_err(|_| error())?;
+        }
+
+        match bytes.next() {
+            Some(_) => Err(error()),
+            None => Ok(MacAddr(mac)),
+        }
+    }
+}
+
+/// A SSID, up to 32 bytes which are not necessarily UTF-8
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Ssid(pub Vec<u8>);
+
+impl Ssid {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Empty SSID, as advertised by hidden networks
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<&str> for Ssid {
+    fn from(ssid: &str) -> Ssid {
+        Ssid(ssid.as_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Ssid {
+    fn from(ssid: Vec<u8>) -> Ssid {
+        Ssid(ssid)
+    }
+}
+
+impl fmt::Display for Ssid {
+    /// Same escaping as iw: bytes which are not printable ASCII are written as `\xNN`, so are
+    /// backslashes and leading or trailing spaces
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let last = self.0.len().saturating_sub(1);
+        for (i, byte) in self.0.iter().enumerate() {
+            let inner_space = *byte == b' ' && i != 0 && i != last;
+            if (byte.is_ascii_graphic() && *byte != b'\\') || inner_space {
+                write!(f, "{}", *byte as char)?;
+            } else {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A frequency in MHz
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Mhz(pub u32);
+
+impl Mhz {
+    /// Frequency in GHz
+    pub fn ghz(&self) -> f64 {
+        self.0 as f64 / 1000.0
+    }
+
+    /// Channel number of a frequency in the 2.4, 5, 6 or 60 GHz bands
+    pub fn channel(&self) -> Option<u32> {
+        let freq = self.0;
+        let channel = match freq {
+            2484 => 14,
+            2407..=2483 => (freq - 2407) / 5,
+            4910..=4980 => (freq - 4000) / 5,
+            5000..=5924 => (freq - 5000) / 5,
+            5935 => 2,
+            5950..=45000 => (freq - 5950) / 5,
+            58320..=70200 => (freq - 56160) / 2160,
+            _ => 0,
+        };
+
+        if channel == 0 {
+            None
+        } else {
+            Some(channel)
+        }
+    }
+}
+
+impl fmt::Display for Mhz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} MHz", self.0)
+    }
+}
+
+/// A power level in mBm, hundredths of dBm
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Mbm(pub i32);
+
+impl Mbm {
+    /// Power level in dBm, with the precision of mBm
+    pub fn dbm(&self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+}
+
+impl From<Dbm> for Mbm {
+    fn from(dbm: Dbm) -> Mbm {
+        Mbm(dbm.0 * 100)
+    }
+}
+
+impl fmt::Display for Mbm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} dBm", self.dbm())
+    }
+}
+
+/// A power level or signal strength in dBm
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Dbm(pub i32);
+
+impl Dbm {
+    /// Power level in mW
+    pub fn milliwatts(&self) -> f64 {
+        10f64.powf(self.0 as f64 / 10.0)
+    }
+}
+
+impl From<Mbm> for Dbm {
+    /// Truncated to a whole dBm
+    fn from(mbm: Mbm) -> Dbm {
+        Dbm(mbm.0 / 100)
+    }
+}
+
+impl fmt::Display for Dbm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} dBm", self.0)
+    }
+}
+
+/// A duration in time units (TU) of 1024 microseconds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tu(pub u32);
+
+impl From<Tu> for Duration {
+    fn from(tu: Tu) -> Duration {
+        Duration::from_micros(tu.0 as u64 * 1024)
+    }
+}
+
+impl fmt::Display for Tu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} TU", self.0)
+    }
+}
+
+#[cfg(test)]
+mod test_types {
+    use super::*;
+
+    #[test]
+    fn test_mac_addr() {
+        let mac: MacAddr = "02:00:5e:10:00:0A".parse().unwrap();
+        assert_eq!(mac, MacAddr([0x02, 0x00, 0x5e, 0x10, 0x00, 0x0a]));
+        assert_eq!(mac.to_string(), "02:00:5E:10:00:0A");
+        assert_eq!("02-00-5e-10-00-0a".parse::<MacAddr>(), Ok(mac));
+        assert!(mac.is_locally_administered());
+        assert!(mac.is_unicast());
+
+        assert!(MacAddr::BROADCAST.is_broadcast());
+        assert!(MacAddr::BROADCAST.is_multicast());
+        assert!(MacAddr([0x01, 0x00, 0x5e, 0, 0, 1]).is_multicast());
+
+        let invalid = vec![
+            "",
+            "02:00:5e:10:00",
+            "02:00:5e:10:00:0a:01",
+            "2:00:5e:10:00:0a",
+            "zz:00:5e:10:00:0a",
+        ];
+        for mac in invalid {
+            assert!(mac.parse::<MacAddr>().is_err(), "{}", mac);
+        }
+    }
+
+    #[test]
+    fn test_ssid_escaping() {
+        assert_eq!(Ssid::from("eduroam").to_string(), "eduroam");
+        assert_eq!(Ssid::from("my wifi").to_string(), "my wifi");
+        assert_eq!(Ssid::from(" wifi ").to_string(), "\\x20wifi\\x20");
+        assert_eq!(Ssid::from("a\\b").to_string(), "a\\x5cb");
+        assert_eq!(Ssid(vec![0x63, 0x61, 0x66, 0xe9, 0x00]).to_string(), "caf\\xe9\\x00");
+        assert!(Ssid::default().is_empty());
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(Mhz(2412).channel(), Some(1));
+        assert_eq!(Mhz(2484).channel(), Some(14));
+        assert_eq!(Mhz(5180).channel(), Some(36));
+        assert_eq!(Mhz(5955).channel(), Some(1));
+        assert_eq!(Mhz(60480).channel(), Some(2));
+        assert_eq!(Mhz(1000).channel(), None);
+        assert_eq!(Mhz(5180).ghz(), 5.18);
+
+        assert_eq!(Mbm::from(Dbm(-53)), Mbm(-5300));
+        assert_eq!(Dbm::from(Mbm(1750)), Dbm(17));
+        assert_eq!(Mbm(1750).to_string(), "17.50 dBm");
+        assert_eq!(Dbm(0).milliwatts(), 1.0);
+        assert!((Dbm(20).milliwatts() - 100.0).abs() < 1e-9);
+
+        assert_eq!(Duration::from(Tu(100)), Duration::from_micros(102_400));
+    }
+}
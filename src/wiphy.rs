@@ -0,0 +1,640 @@
+use crate::attr::{
+    NlaNested, Nl80211Attr, Nl80211BandAttr, Nl80211BandIftypeAttr, Nl80211Bandc,
+    Nl80211BitrateAttr, Nl80211ChanWidth, Nl80211ExtFeatureIndex, Nl80211FeatureFlags,
+    Nl80211FrequencyAttr, Nl80211IfCombinationAttrs, Nl80211IfaceLimitAttrs, Nl80211Iftype,
+};
+use crate::channel::Channel;
+use crate::cmd::Nl80211Cmd;
+use crate::error::{AttrError, Nl80211Error};
+use crate::ie::{Cipher, HeCapabilities, HtCapabilities, VhtCapabilities};
+use crate::nl80211traits::ParseNlAttr;
+use crate::parse_attr::{fixed_payload, parse_string, parse_u16, parse_u32, parse_u8};
+use crate::types::Mhz;
+use neli::nlattr::{AttrHandle, Nlattr};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A radio and its capabilities, see [`Socket::get_wiphys`](struct.Socket.html#method.get_wiphys)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Wiphy {
+    /// Wiphy index, the N of the default phyN name
+    pub index: u32,
+    pub name: String,
+    pub bands: Vec<Band>,
+    /// Interface types which can be created on the wiphy
+    pub iftypes: Vec<Nl80211Iftype>,
+    /// Commands supported by the driver
+    pub commands: Vec<Nl80211Cmd>,
+    /// Cipher suites supported for keys
+    pub ciphers: Vec<Cipher>,
+    /// Maximum number of SSIDs probed by a scan
+    pub max_scan_ssids: Option<u8>,
+    /// Maximum length of the information elements added to probe requests
+    pub max_scan_ie_len: Option<u16>,
+    /// Maximum number of SSIDs probed by a scheduled scan
+    pub max_sched_scan_ssids: Option<u8>,
+    /// Maximum number of match sets of a scheduled scan
+    pub max_match_sets: Option<u8>,
+    /// Antennas available for transmission, one bit per antenna
+    pub antenna_avail_tx: Option<u32>,
+    /// Antennas available for reception, one bit per antenna
+    pub antenna_avail_rx: Option<u32>,
+    /// Antennas configured for transmission
+    pub antenna_tx: Option<u32>,
+    /// Antennas configured for reception
+    pub antenna_rx: Option<u32>,
+    /// Valid combinations of interfaces running at the same time
+    pub interface_combinations: Vec<InterfaceCombination>,
+    /// Bitmask of Nl80211FeatureFlags
+    pub feature_flags: u32,
+    /// Bitmap indexed by Nl80211ExtFeatureIndex
+    pub ext_features: Vec<u8>,
+}
+
+impl Wiphy {
+    /// The driver advertises a feature
+    pub fn has_feature(&self, feature: Nl80211FeatureFlags) -> bool {
+        self.feature_flags & u32::from(u16::from(feature)) != 0
+    }
+
+    /// The driver advertises an extended feature
+    pub fn has_ext_feature(&self, feature: Nl80211ExtFeatureIndex) -> bool {
+        let index = u16::from(feature) as usize;
+        self.ext_features
+            .get(index / 8)
+            .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+    }
+
+    /// The driver supports a command
+    pub fn supports_command(&self, cmd: Nl80211Cmd) -> bool {
+        self.commands.contains(&cmd)
+    }
+
+    /// Band of the wiphy, if supported
+    pub fn band(&self, band: Nl80211Bandc) -> Option<&Band> {
+        self.bands.iter().find(|b| b.band == band)
+    }
+
+    /// Channel of any band, by center frequency
+    pub fn channel(&self, frequency: Mhz) -> Option<&Channel> {
+        self.bands.iter().find_map(|band| band.channel(frequency))
+    }
+
+    /// Merge the consecutive messages of a split dump describing the same wiphy
+    pub(crate) fn merge_split_dump(parts: Vec<Wiphy>) -> Vec<Wiphy> {
+        let mut wiphys: Vec<Wiphy> = Vec::new();
+
+        for part in parts {
+            match wiphys.last_mut() {
+                Some(wiphy) if wiphy.index == part.index => wiphy.merge(part),
+                _ => wiphys.push(part),
+            }
+        }
+
+        wiphys
+    }
+
+    /// Fold a message of a split dump into the wiphy it continues
+    pub(crate) fn merge(&mut self, other: Wiphy) {
+        if self.name.is_empty() {
+            self.name = other.name;
+        }
+
+        for band in other.bands {
+            self.merge_band(band);
+        }
+
+        self.iftypes.extend(other.iftypes);
+        self.commands.extend(other.commands);
+        self.ciphers.extend(other.ciphers);
+        self.max_scan_ssids = self.max_scan_ssids.or(other.max_scan_ssids);
+        self.max_scan_ie_len = self.max_scan_ie_len.or(other.max_scan_ie_len);
+        self.max_sched_scan_ssids = self.max_sched_scan_ssids.or(other.max_sched_scan_ssids);
+        self.max_match_sets = self.max_match_sets.or(other.max_match_sets);
+        self.antenna_avail_tx = self.antenna_avail_tx.or(other.antenna_avail_tx);
+        self.antenna_avail_rx = self.antenna_avail_rx.or(other.antenna_avail_rx);
+        self.antenna_tx = self.antenna_tx.or(other.antenna_tx);
+        self.antenna_rx = self.antenna_rx.or(other.antenna_rx);
+        self.interface_combinations.extend(other.interface_combinations);
+        self.feature_flags |= other.feature_flags;
+
+        if self.ext_features.is_empty() {
+            self.ext_features = other.ext_features;
+        }
+    }
+
+    /// A band is split across messages when it has many channels
+    fn merge_band(&mut self, band: Band) {
+        match self.bands.iter_mut().find(|b| b.band == band.band) {
+            Some(existing) => existing.merge(band),
+            None => self.bands.push(band),
+        }
+    }
+}
+
+impl ParseNlAttr for Wiphy {
+    /// Parse one message of a wiphy dump, a split dump has several for the same wiphy
+    fn parse(&mut self, handle: AttrHandle<Nl80211Attr>) -> Result<Wiphy, Nl80211Error> {
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211Attr::AttrWiphy => self.index = parse_u32(attr)?,
+                Nl80211Attr::AttrWiphyName => {
+                    self.name = parse_string(&attr.payload).trim_end_matches('\0').to_string()
+                }
+                Nl80211Attr::AttrWiphyBands => {
+                    let bands_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for band_attr in bands_handle.iter() {
+                        let band = Band::parse(band_attr)?;
+                        self.merge_band(band);
+                    }
+                }
+                Nl80211Attr::AttrSupportedIftypes => {
+                    let iftypes_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    self.iftypes.extend(parse_iftypes(iftypes_handle))
+                }
+                Nl80211Attr::AttrSupportedCommands => {
+                    let commands_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for cmd_attr in commands_handle.iter() {
+                        // Values too large for the enumeration are unknown, not truncated
+                        let cmd = u8::try_from(parse_u32(cmd_attr)?).unwrap_or(u8::MAX);
+                        self.commands.push(Nl80211Cmd::from(cmd))
+                    }
+                }
+                Nl80211Attr::AttrCipherSuites => self.ciphers.extend(parse_ciphers(attr)?),
+                Nl80211Attr::AttrMaxNumScanSsids => self.max_scan_ssids = Some(parse_u8(attr)?),
+                Nl80211Attr::AttrMaxScanIeLen => self.max_scan_ie_len = Some(parse_u16(attr)?),
+                Nl80211Attr::AttrMaxNumSchedScanSsids => {
+                    self.max_sched_scan_ssids = Some(parse_u8(attr)?)
+                }
+                Nl80211Attr::AttrMaxMatchSets => self.max_match_sets = Some(parse_u8(attr)?),
+                Nl80211Attr::AttrWiphyAntennaAvailTx => {
+                    self.antenna_avail_tx = Some(parse_u32(attr)?)
+                }
+                Nl80211Attr::AttrWiphyAntennaAvailRx => {
+                    self.antenna_avail_rx = Some(parse_u32(attr)?)
+                }
+                Nl80211Attr::AttrWiphyAntennaTx => self.antenna_tx = Some(parse_u32(attr)?),
+                Nl80211Attr::AttrWiphyAntennaRx => self.antenna_rx = Some(parse_u32(attr)?),
+                Nl80211Attr::AttrInterfaceCombinations => {
+                    let combinations_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for combination_attr in combinations_handle.iter() {
+                        let combination_handle = combination_attr
+                            .get_nested_attributes::<Nl80211IfCombinationAttrs>()?;
+                        self.interface_combinations
+                            .push(InterfaceCombination::parse(combination_handle)?)
+                    }
+                }
+                Nl80211Attr::AttrFeatureFlags => self.feature_flags = parse_u32(attr)?,
+                Nl80211Attr::AttrExtFeatures => self.ext_features = attr.payload.clone(),
+                _ => (),
+            }
+        }
+
+        Ok(self.to_owned())
+    }
+}
+
+impl fmt::Display for Wiphy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut result = Vec::new();
+
+        result.push(format!("wiphy : {}", self.name));
+        result.push(format!("index : {}", self.index));
+
+        let bands: Vec<String> = self.bands.iter().map(|band| band.name().to_string()).collect();
+        result.push(format!("bands : {}", bands.join(", ")));
+
+        if !self.ciphers.is_empty() {
+            let ciphers: Vec<String> = self.ciphers.iter().map(|c| c.to_string()).collect();
+            result.push(format!("ciphers : {}", ciphers.join(", ")))
+        };
+
+        if let Some(max_scan_ssids) = self.max_scan_ssids {
+            result.push(format!("max scan SSIDs : {}", max_scan_ssids))
+        };
+
+        write!(f, "{}", result.join("\n"))
+    }
+}
+
+/// A frequency band supported by a wiphy
+#[derive(Clone, Debug, PartialEq)]
+pub struct Band {
+    pub band: Nl80211Bandc,
+    pub channels: Vec<Channel>,
+    pub bitrates: Vec<Bitrate>,
+    pub ht_capabilities: Option<HtCapabilities>,
+    pub vht_capabilities: Option<VhtCapabilities>,
+    /// HE capabilities, which can differ between interface types
+    pub iftype_data: Vec<IftypeData>,
+}
+
+impl Band {
+    /// Name of the band, e.g. "2.4 GHz"
+    pub fn name(&self) -> &'static str {
+        match self.band {
+            Nl80211Bandc::Band2ghz => "2.4 GHz",
+            Nl80211Bandc::Band5ghz => "5 GHz",
+            Nl80211Bandc::Band60ghz => "60 GHz",
+            Nl80211Bandc::Band6ghz => "6 GHz",
+            _ => "unknown",
+        }
+    }
+
+    /// Channel of the band, by center frequency
+    pub fn channel(&self, frequency: Mhz) -> Option<&Channel> {
+        self.channels.iter().find(|channel| channel.frequency == frequency)
+    }
+
+    /// Channels where an access point can be started with a width
+    pub fn ap_channels(&self, width: Nl80211ChanWidth) -> Vec<&Channel> {
+        self.channels
+            .iter()
+            .filter(|channel| channel.usable_for_ap(width.clone()))
+            .collect()
+    }
+
+    fn parse(band_attr: &Nlattr<NlaNested, Vec<u8>>) -> Result<Band, Nl80211Error> {
+        let mut band = Band {
+            band: Nl80211Bandc::from(u16::from(band_attr.nla_type.clone())),
+            channels: Vec::new(),
+            bitrates: Vec::new(),
+            ht_capabilities: None,
+            vht_capabilities: None,
+            iftype_data: Vec::new(),
+        };
+        let mut ht_capa = None;
+        let mut ht_mcs_set = None;
+        let mut ampdu_factor = 0;
+        let mut ampdu_density = 0;
+        let mut vht_capa = None;
+        let mut vht_mcs_set = None;
+
+        let handle = band_attr.get_nested_attributes::<Nl80211BandAttr>()?;
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211BandAttr::BandAttrFreqs => {
+                    let freqs_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for freq_attr in freqs_handle.iter() {
+                        let freq_handle =
+                            freq_attr.get_nested_attributes::<Nl80211FrequencyAttr>()?;
+                        band.channels.push(Channel::parse(freq_handle)?)
+                    }
+                }
+                Nl80211BandAttr::BandAttrRates => {
+                    let rates_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for rate_attr in rates_handle.iter() {
+                        let rate_handle =
+                            rate_attr.get_nested_attributes::<Nl80211BitrateAttr>()?;
+                        band.bitrates.push(Bitrate::parse(rate_handle)?)
+                    }
+                }
+                Nl80211BandAttr::BandAttrHtCapa => ht_capa = Some(parse_u16(attr)?),
+                Nl80211BandAttr::BandAttrHtMcsSet => {
+                    let mut mcs_set = [0; 16];
+                    mcs_set.copy_from_slice(fixed_payload(attr, 16)?);
+                    ht_mcs_set = Some(mcs_set)
+                }
+                Nl80211BandAttr::BandAttrHtAmpduFactor => ampdu_factor = parse_u8(attr)?,
+                Nl80211BandAttr::BandAttrHtAmpduDensity => ampdu_density = parse_u8(attr)?,
+                Nl80211BandAttr::BandAttrVhtCapa => vht_capa = Some(fixed_payload(attr, 4)?),
+                Nl80211BandAttr::BandAttrVhtMcsSet => {
+                    vht_mcs_set = Some(fixed_payload(attr, 8)?)
+                }
+                Nl80211BandAttr::BandAttrIftypeData => {
+                    let data_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for data_attr in data_handle.iter() {
+                        let iftype_handle =
+                            data_attr.get_nested_attributes::<Nl80211BandIftypeAttr>()?;
+                        band.iftype_data.push(IftypeData::parse(iftype_handle)?)
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let (Some(cap_info), Some(mcs_set)) = (ht_capa, ht_mcs_set) {
+            band.ht_capabilities = Some(HtCapabilities {
+                cap_info,
+                ampdu_params: (ampdu_factor & 0x3) | (ampdu_density & 0x7) << 2,
+                mcs_set,
+                extended_cap: 0,
+                txbf_cap: 0,
+                asel_cap: 0,
+            })
+        }
+
+        if let (Some(capa), Some(mcs_set)) = (vht_capa, vht_mcs_set) {
+            band.vht_capabilities = VhtCapabilities::parse(&[capa, mcs_set].concat())
+        }
+
+        Ok(band)
+    }
+
+    fn merge(&mut self, other: Band) {
+        self.channels.extend(other.channels);
+        self.bitrates.extend(other.bitrates);
+        self.ht_capabilities = self.ht_capabilities.take().or(other.ht_capabilities);
+        self.vht_capabilities = self.vht_capabilities.take().or(other.vht_capabilities);
+        self.iftype_data.extend(other.iftype_data);
+    }
+}
+
+/// A legacy bitrate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bitrate {
+    /// Rate in units of 100 kbit/s
+    pub rate: u32,
+    /// Short preamble is supported in the 2.4 GHz band
+    pub short_preamble: bool,
+}
+
+impl Bitrate {
+    /// Rate in Mbit/s
+    pub fn mbps(&self) -> f64 {
+        self.rate as f64 / 10.0
+    }
+
+    fn parse(handle: AttrHandle<Nl80211BitrateAttr>) -> Result<Bitrate, AttrError> {
+        let mut bitrate = Bitrate { rate: 0, short_preamble: false };
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211BitrateAttr::BitrateAttrRate => bitrate.rate = parse_u32(attr)?,
+                Nl80211BitrateAttr::BitrateAttr2ghzShortpreamble => bitrate.short_preamble = true,
+                _ => (),
+            }
+        }
+
+        Ok(bitrate)
+    }
+}
+
+/// Capabilities shared by some interface types of a band
+#[derive(Clone, Debug, PartialEq)]
+pub struct IftypeData {
+    pub iftypes: Vec<Nl80211Iftype>,
+    pub he_capabilities: Option<HeCapabilities>,
+}
+
+impl IftypeData {
+    fn parse(handle: AttrHandle<Nl80211BandIftypeAttr>) -> Result<IftypeData, Nl80211Error> {
+        let mut iftypes = Vec::new();
+        let mut mac_cap = None;
+        let mut phy_cap = None;
+        let mut mcs_set = None;
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211BandIftypeAttr::BandIftypeAttrIftypes => {
+                    iftypes = parse_iftypes(attr.get_nested_attributes::<NlaNested>()?)
+                }
+                Nl80211BandIftypeAttr::BandIftypeAttrHeCapMac => {
+                    mac_cap = Some(fixed_payload(attr, 6)?)
+                }
+                Nl80211BandIftypeAttr::BandIftypeAttrHeCapPhy => {
+                    phy_cap = Some(fixed_payload(attr, 11)?)
+                }
+                Nl80211BandIftypeAttr::BandIftypeAttrHeCapMcsSet => {
+                    mcs_set = Some(attr.payload.as_slice())
+                }
+                _ => (),
+            }
+        }
+
+        let he_capabilities = match (mac_cap, phy_cap, mcs_set) {
+            (Some(mac_cap), Some(phy_cap), Some(mcs_set)) => {
+                HeCapabilities::parse(&[mac_cap, phy_cap, mcs_set].concat())
+            }
+            _ => None,
+        };
+
+        Ok(IftypeData { iftypes, he_capabilities })
+    }
+}
+
+/// A valid combination of interfaces running at the same time
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceCombination {
+    pub limits: Vec<InterfaceLimit>,
+    /// Maximum number of interfaces in total
+    pub max_interfaces: u32,
+    /// Number of different channels which can be used at the same time
+    pub num_channels: u32,
+    /// Beacon intervals of access points must match
+    pub beacon_interval_match: bool,
+    /// Bitmask of the Nl80211ChanWidth supporting radar detection
+    pub radar_detect_widths: u32,
+    /// Bitmask of the DFS regions supporting radar detection
+    pub radar_detect_regions: u32,
+}
+
+impl InterfaceCombination {
+    fn parse(
+        handle: AttrHandle<Nl80211IfCombinationAttrs>,
+    ) -> Result<InterfaceCombination, Nl80211Error> {
+        let mut combination = InterfaceCombination::default();
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211IfCombinationAttrs::IfaceCombLimits => {
+                    let limits_handle = attr.get_nested_attributes::<NlaNested>()?;
+                    for limit_attr in limits_handle.iter() {
+                        let limit_handle =
+                            limit_attr.get_nested_attributes::<Nl80211IfaceLimitAttrs>()?;
+                        combination.limits.push(InterfaceLimit::parse(limit_handle)?)
+                    }
+                }
+                Nl80211IfCombinationAttrs::IfaceCombMaxnum => {
+                    combination.max_interfaces = parse_u32(attr)?
+                }
+                Nl80211IfCombinationAttrs::IfaceCombStaApBiMatch => {
+                    combination.beacon_interval_match = true
+                }
+                Nl80211IfCombinationAttrs::IfaceCombNumChannels => {
+                    combination.num_channels = parse_u32(attr)?
+                }
+                Nl80211IfCombinationAttrs::IfaceCombRadarDetectWidths => {
+                    combination.radar_detect_widths = parse_u32(attr)?
+                }
+                Nl80211IfCombinationAttrs::IfaceCombRadarDetectRegions => {
+                    combination.radar_detect_regions = parse_u32(attr)?
+                }
+                _ => (),
+            }
+        }
+
+        Ok(combination)
+    }
+}
+
+/// At most `max` interfaces of the given types in a combination
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceLimit {
+    pub max: u32,
+    pub iftypes: Vec<Nl80211Iftype>,
+}
+
+impl InterfaceLimit {
+    fn parse(handle: AttrHandle<Nl80211IfaceLimitAttrs>) -> Result<InterfaceLimit, Nl80211Error> {
+        let mut limit = InterfaceLimit::default();
+
+        for attr in handle.iter() {
+            match attr.nla_type {
+                Nl80211IfaceLimitAttrs::IfaceLimitMax => limit.max = parse_u32(attr)?,
+                Nl80211IfaceLimitAttrs::IfaceLimitTypes => {
+                    limit.iftypes = parse_iftypes(attr.get_nested_attributes::<NlaNested>()?)
+                }
+                _ => (),
+            }
+        }
+
+        Ok(limit)
+    }
+}
+
+/// Interface types are flags, given by the type of the nested attributes
+fn parse_iftypes(handle: AttrHandle<NlaNested>) -> Vec<Nl80211Iftype> {
+    handle
+        .iter()
+        .map(|attr| Nl80211Iftype::from(u16::from(attr.nla_type.clone())))
+        .collect()
+}
+
+/// Cipher suites are a packed array of u32 selectors
+fn parse_ciphers(attr: &Nlattr<Nl80211Attr, Vec<u8>>) -> Result<Vec<Cipher>, AttrError> {
+    let len = attr.payload.len();
+    if !len.is_multiple_of(4) {
+        return Err(AttrError {
+            attr: format!("{:?}", attr.nla_type),
+            expected: len + 4 - len % 4,
+            actual: len,
+        });
+    }
+
+    Ok(attr
+        .payload
+        .chunks(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .map(Cipher::from_selector)
+        .collect())
+}
+
+#[cfg(test)]
+mod test_wiphy {
+    use super::*;
+    use crate::scan::put_attr;
+
+    fn nested(entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            put_attr(&mut payload, index as u16, entry);
+        }
+        payload
+    }
+
+    fn band(band: u16, frequencies: &[u32]) -> Vec<u8> {
+        let freqs: Vec<Vec<u8>> = frequencies
+            .iter()
+            .map(|frequency| {
+                let mut freq = Vec::new();
+                put_attr(&mut freq, 1, &frequency.to_le_bytes());
+                freq
+            })
+            .collect();
+
+        let mut band_attrs = Vec::new();
+        put_attr(&mut band_attrs, 1, &nested(&freqs));
+
+        let mut bands = Vec::new();
+        put_attr(&mut bands, band, &band_attrs);
+        bands
+    }
+
+    fn attr(nla_type: Nl80211Attr, payload: Vec<u8>) -> Nlattr<Nl80211Attr, Vec<u8>> {
+        Nlattr::new(None, nla_type, payload).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut bitrate = Vec::new();
+        put_attr(&mut bitrate, 1, &10u32.to_le_bytes());
+        put_attr(&mut bitrate, 2, &[]);
+
+        let mut band_attrs = Vec::new();
+        put_attr(&mut band_attrs, 2, &nested(&[bitrate]));
+        put_attr(&mut band_attrs, 4, &0x19efu16.to_le_bytes());
+        put_attr(&mut band_attrs, 3, &[0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        put_attr(&mut band_attrs, 5, &[3]);
+        put_attr(&mut band_attrs, 6, &[6]);
+        let mut bands = Vec::new();
+        put_attr(&mut bands, 0, &band_attrs);
+
+        let handler = vec![
+            attr(Nl80211Attr::AttrWiphy, vec![0, 0, 0, 0]),
+            attr(Nl80211Attr::AttrWiphyName, b"phy0\0".to_vec()),
+            attr(Nl80211Attr::AttrMaxNumScanSsids, vec![20]),
+            attr(Nl80211Attr::AttrWiphyBands, bands),
+            attr(Nl80211Attr::AttrSupportedIftypes, nested(&[vec![], vec![], vec![], vec![]])),
+            attr(Nl80211Attr::AttrCipherSuites, vec![4, 0xac, 0x0f, 0, 2, 0xac, 0x0f, 0]),
+            attr(Nl80211Attr::AttrSupportedCommands, nested(&[vec![32, 0, 0, 0]])),
+            attr(Nl80211Attr::AttrFeatureFlags, vec![0x80, 0, 0, 0]),
+            attr(Nl80211Attr::AttrExtFeatures, vec![0x02]),
+        ];
+
+        let wiphy = Wiphy::default().parse(AttrHandle::Owned(handler)).unwrap();
+
+        assert_eq!(wiphy.index, 0);
+        assert_eq!(wiphy.name, "phy0");
+        assert_eq!(wiphy.max_scan_ssids, Some(20));
+        assert_eq!(wiphy.iftypes[3], Nl80211Iftype::IftypeAp);
+        assert_eq!(wiphy.ciphers, vec![Cipher::Ccmp128, Cipher::Tkip]);
+        assert!(wiphy.supports_command(Nl80211Cmd::CmdGetScan));
+        assert!(wiphy.has_feature(Nl80211FeatureFlags::FeatureScanFlush));
+        assert!(!wiphy.has_feature(Nl80211FeatureFlags::FeatureSae));
+        assert!(wiphy.has_ext_feature(Nl80211ExtFeatureIndex::ExtFeatureRrm));
+
+        let band = wiphy.band(Nl80211Bandc::Band2ghz).unwrap();
+        assert_eq!(band.bitrates, vec![Bitrate { rate: 10, short_preamble: true }]);
+        let ht = band.ht_capabilities.as_ref().unwrap();
+        assert!(ht.supports_40mhz());
+        assert_eq!(ht.ampdu_params, 0x1b);
+        assert_eq!(ht.rx_spatial_streams(), 2);
+    }
+
+    #[test]
+    fn test_merge() {
+        let first = vec![
+            attr(Nl80211Attr::AttrWiphy, vec![1, 0, 0, 0]),
+            attr(Nl80211Attr::AttrWiphyName, b"phy1\0".to_vec()),
+            attr(Nl80211Attr::AttrWiphyBands, band(0, &[2412, 2417])),
+        ];
+        let second = vec![
+            attr(Nl80211Attr::AttrWiphy, vec![1, 0, 0, 0]),
+            attr(Nl80211Attr::AttrWiphyBands, band(0, &[2422])),
+        ];
+        let third = vec![
+            attr(Nl80211Attr::AttrWiphy, vec![1, 0, 0, 0]),
+            attr(Nl80211Attr::AttrWiphyBands, band(1, &[5180])),
+        ];
+
+        let mut wiphy = Wiphy::default().parse(AttrHandle::Owned(first)).unwrap();
+        wiphy.merge(Wiphy::default().parse(AttrHandle::Owned(second)).unwrap());
+        wiphy.merge(Wiphy::default().parse(AttrHandle::Owned(third)).unwrap());
+
+        assert_eq!(wiphy.name, "phy1");
+        assert_eq!(wiphy.bands.len(), 2);
+        let frequencies: Vec<Mhz> = wiphy.bands[0].channels.iter().map(|c| c.frequency).collect();
+        assert_eq!(frequencies, vec![Mhz(2412), Mhz(2417), Mhz(2422)]);
+        assert_eq!(wiphy.bands[1].name(), "5 GHz");
+        assert_eq!(wiphy.channel(Mhz(5180)).unwrap().number(), Some(36));
+        assert!(wiphy.channel(Mhz(5200)).is_none());
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        let handler = vec![attr(Nl80211Attr::AttrCipherSuites, vec![4, 0xac, 0x0f])];
+
+        assert!(Wiphy::default().parse(AttrHandle::Owned(handler)).is_err());
+    }
+}